@@ -0,0 +1,79 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Invalid command line; reported together with the usage text
+pub struct UsageError(pub String);
+
+/// Options of one subcommand: `--name value` pairs and positional arguments
+///
+/// Each accessor removes what it reads, so `finish` can refuse whatever is left.
+pub struct Args {
+    options: Vec<(String, String)>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    /// Split `args`; every `--option` takes a value
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, UsageError> {
+        let mut parsed = Self {
+            options: Vec::new(),
+            positional: Vec::new(),
+        };
+        while let Some(arg) = args.next() {
+            if arg.starts_with("--") {
+                let value = args.next().ok_or_else(|| UsageError(format!("Missing value for {}", arg)))?;
+                parsed.options.push((arg, value));
+            } else {
+                parsed.positional.push(arg);
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Value of an option that may be given at most once
+    pub fn value(&mut self, name: &str) -> Result<Option<String>, UsageError> {
+        let mut values = self.values(name);
+        match values.len() {
+            0 => Ok(None),
+            1 => Ok(values.pop()),
+            _ => Err(UsageError(format!("{} given more than once", name))),
+        }
+    }
+
+    pub fn required(&mut self, name: &str) -> Result<String, UsageError> {
+        self.value(name)?.ok_or_else(|| UsageError(format!("Missing {}", name)))
+    }
+
+    /// Every value of an option that may be repeated, in order
+    pub fn values(&mut self, name: &str) -> Vec<String> {
+        let (matching, rest) = std::mem::take(&mut self.options)
+            .into_iter()
+            .partition(|(option, _)| option == name);
+        self.options = rest;
+        matching.into_iter().map(|(_, value)| value).collect()
+    }
+
+    /// Value of an option parsed as `T`
+    pub fn parsed<T>(&mut self, name: &str) -> Result<Option<T>, UsageError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.value(name)?
+            .map(|value| value.parse().map_err(|e| UsageError(format!("Invalid {} {:?}: {}", name, value, e))))
+            .transpose()
+    }
+
+    pub fn positional(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.positional)
+    }
+
+    /// Refuse options and arguments the subcommand did not read
+    pub fn finish(self) -> Result<(), UsageError> {
+        match (self.options.first(), self.positional.first()) {
+            (Some((option, _)), _) => Err(UsageError(format!("Unknown option: {}", option))),
+            (_, Some(arg)) => Err(UsageError(format!("Unexpected argument: {}", arg))),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,629 @@
+mod args;
+#[cfg(feature = "prove")]
+mod prove;
+
+use frost_ed25519 as frost;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use threshold_signing_lib::keystore::{self, KEYSTORE_MAGIC, KeystoreKind};
+use threshold_signing_lib::serialization::ENVELOPE_MAGIC;
+use threshold_signing_lib::{
+    CombinedSignature, DerivationPath, DkgParticipant, ExtendedVerifyingKey, InMemoryTransport, PublicCoordinator, SignerTransport,
+    TcpTransport, ThresholdError, ThresholdSigner, decode, encode, generate_frost_keys,
+};
+
+use args::{Args, UsageError};
+
+const USAGE: &str = "Usage: threshold-cli <command> [options]
+
+Commands:
+  keygen        Generate a group with a trusted dealer: one encrypted key file per signer and
+                the public key package
+                --threshold <t> --participants <n> [--out-dir <dir>] [--group-out <path>]
+  keygen dkg-round1|dkg-round2|dkg-finish
+                Run one party of a distributed key generation, one step at a time
+                dkg-round1 --index <i> --threshold <t> --participants <n> [--out-dir <dir>]
+                dkg-round2 --index <i> [--out-dir <dir>]
+                dkg-finish --index <i> [--out-dir <dir>] [--group-out <path>]
+  sign          Sign a message with local key files or remote signer daemons
+                --group <group.pub> (--key <file>... | --signer <index>=<host:port>...)
+                [--threshold <t>] [--participants <i,j,...>] [--path <m/...>] [--out <file>]
+                [--message-file <path>|- | --message-hex <hex>]
+  verify        Check a signature file, optionally against the group (or child) key
+                --signature <file> [--group <group.pub>] [--path <m/...>]
+                [--message-file <path>|- | --message-hex <hex>]
+  inspect       Describe key files and signature files
+                <file>...
+  prove         Prove in the SP1 zkVM that a signature was checked
+                --signature <file> [--out <proof.bin>] [--vk-out <vk.bin>]
+                [--message-file <path>|- | --message-hex <hex>]
+  verify-proof  Check an SP1 proof and print its public values
+                --proof <proof.bin> --vk <vk.bin> [--message-file <path>|- | --message-hex <hex>]
+
+sign, verify and prove read the message from stdin when neither --message-file nor --message-hex
+is given; verify-proof then only prints the proven message.
+Key files and DKG state files are encrypted with the password from --password-file or the THRESHOLD_SIGNER_PASSWORD
+environment variable, like the signer daemon's.
+Signatures are written as binary envelopes, or printed as hex without --out; both are accepted as input.
+keygen without a step sees every share it writes. Without a trusted dealer, every party runs the
+dkg-* steps on its own machine, in its own --out-dir:
+  dkg-round1  writes dkg-round1-<i>.bin for every other party and keeps its secret in dkg-<i>.state
+  dkg-round2  reads everyone's dkg-round1-<j>.bin and writes dkg-round2-<i>-to-<j>.bin for party j
+  dkg-finish  reads every dkg-round2-<j>-to-<i>.bin, writes signer-<i>.key and the group package,
+              and deletes dkg-<i>.state
+Round 2 files hold secret shares: hand each one only to its recipient, over a private channel.
+Every party should compare the group key dkg-finish prints with the others'.";
+
+/// Environment variable consulted when no --password-file is given
+const PASSWORD_ENV: &str = "THRESHOLD_SIGNER_PASSWORD";
+
+/// Ciphersuite of every key and signature this tool handles
+type Suite = frost::Ed25519Sha512;
+
+/// Why a command did not succeed
+enum Failure {
+    /// Bad command line: exit code 2, with the usage text
+    Usage(String),
+    /// The command ran and failed, or found an invalid signature or proof: exit code 1
+    Failed(String),
+}
+
+impl From<UsageError> for Failure {
+    fn from(e: UsageError) -> Self {
+        Failure::Usage(e.0)
+    }
+}
+
+impl From<ThresholdError> for Failure {
+    fn from(e: ThresholdError) -> Self {
+        Failure::Failed(e.to_string())
+    }
+}
+
+fn main() {
+    let mut argv = std::env::args().skip(1);
+    let command = argv.next().unwrap_or_default();
+
+    let result = match command.as_str() {
+        "keygen" => Args::parse(argv).map_err(Failure::from).and_then(keygen),
+        "sign" => Args::parse(argv).map_err(Failure::from).and_then(sign),
+        "verify" => Args::parse(argv).map_err(Failure::from).and_then(verify),
+        "inspect" => Args::parse(argv).map_err(Failure::from).and_then(inspect),
+        "prove" => Args::parse(argv).map_err(Failure::from).and_then(prove),
+        "verify-proof" => Args::parse(argv).map_err(Failure::from).and_then(verify_proof),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return;
+        }
+        "" => Err(Failure::Usage("Missing command".to_string())),
+        other => Err(Failure::Usage(format!("Unknown command: {}", other))),
+    };
+
+    match result {
+        Ok(()) => {}
+        Err(Failure::Usage(e)) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+        Err(Failure::Failed(e)) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn keygen(mut args: Args) -> Result<(), Failure> {
+    let mut steps = args.positional().into_iter();
+    let step = steps.next();
+    if let Some(extra) = steps.next() {
+        return Err(Failure::Usage(format!("Unexpected argument: {}", extra)));
+    }
+
+    match step.as_deref() {
+        None => keygen_dealer(args),
+        Some("dkg-round1") => dkg_round1(args),
+        Some("dkg-round2") => dkg_round2(args),
+        Some("dkg-finish") => dkg_finish(args),
+        Some(other) => Err(Failure::Usage(format!("Unknown keygen step: {}", other))),
+    }
+}
+
+fn keygen_dealer(mut args: Args) -> Result<(), Failure> {
+    let threshold: u16 = args.parsed("--threshold")?.ok_or(UsageError("Missing --threshold".to_string()))?;
+    let participants: u16 = args.parsed("--participants")?.ok_or(UsageError("Missing --participants".to_string()))?;
+    let out_dir = PathBuf::from(args.value("--out-dir")?.unwrap_or_else(|| ".".to_string()));
+    let group_out = args.value("--group-out")?.map(PathBuf::from).unwrap_or_else(|| out_dir.join("group.pub"));
+    let password = read_password(&mut args)?;
+    args.finish()?;
+
+    let key_paths: Vec<PathBuf> = (1..=participants)
+        .map(|index| out_dir.join(format!("signer-{}.key", index)))
+        .collect();
+    // Never replace an existing key file: it may hold the only copy of a share
+    refuse_existing(key_paths.iter().chain([&group_out]))?;
+
+    let (key_packages, pubkey_package) = generate_frost_keys::<Suite>(participants, threshold)?;
+
+    std::fs::create_dir_all(&out_dir)
+        .map_err(|e| Failure::Failed(format!("Failed to create {}: {}", out_dir.display(), e)))?;
+    for ((index, key_package), path) in (1..).zip(key_packages).zip(&key_paths) {
+        keystore::save_key_package(path, &ThresholdSigner::new(index, key_package), password.as_bytes())?;
+        println!("Wrote {}", path.display());
+    }
+    keystore::save_public_key_package(&group_out, &pubkey_package)?;
+    println!("Wrote {}", group_out.display());
+
+    println!(
+        "Generated a {}-of-{} group (trusted dealer), group key {}",
+        threshold,
+        participants,
+        hex::encode(verifying_key_bytes(pubkey_package.verifying_key())?)
+    );
+    Ok(())
+}
+
+/// DKG step 1 of party `--index`: broadcast its commitments, keep its secret polynomial
+fn dkg_round1(mut args: Args) -> Result<(), Failure> {
+    let index: u16 = args.parsed("--index")?.ok_or(UsageError("Missing --index".to_string()))?;
+    let threshold: u16 = args.parsed("--threshold")?.ok_or(UsageError("Missing --threshold".to_string()))?;
+    let participants: u16 = args.parsed("--participants")?.ok_or(UsageError("Missing --participants".to_string()))?;
+    let out_dir = PathBuf::from(args.value("--out-dir")?.unwrap_or_else(|| ".".to_string()));
+    let password = read_password(&mut args)?;
+    args.finish()?;
+
+    let state_path = dkg_state_path(&out_dir, index);
+    let round1_path = out_dir.join(dkg_round1_file(index));
+    // A second round 1 would replace the polynomial the others already committed to
+    refuse_existing([&state_path, &round1_path])?;
+
+    let mut participant = DkgParticipant::<Suite>::new(index, participants, threshold)?;
+    let round1_message = participant.start()?;
+
+    std::fs::create_dir_all(&out_dir)
+        .map_err(|e| Failure::Failed(format!("Failed to create {}: {}", out_dir.display(), e)))?;
+    keystore::save_dkg_state(&state_path, &participant, password.as_bytes())?;
+    write_file(&round1_path, &round1_message)?;
+    println!("Wrote {} and {}", state_path.display(), round1_path.display());
+    println!(
+        "Send {} to every other party and put theirs in {}, then run dkg-round2",
+        round1_path.display(),
+        out_dir.display()
+    );
+    Ok(())
+}
+
+/// DKG step 2 of party `--index`: check everyone's commitments, deal a share to each party
+fn dkg_round2(mut args: Args) -> Result<(), Failure> {
+    let index: u16 = args.parsed("--index")?.ok_or(UsageError("Missing --index".to_string()))?;
+    let out_dir = PathBuf::from(args.value("--out-dir")?.unwrap_or_else(|| ".".to_string()));
+    let password = read_password(&mut args)?;
+    args.finish()?;
+
+    let state_path = dkg_state_path(&out_dir, index);
+    let mut participant = keystore::load_dkg_state::<Suite, _>(&state_path, password.as_bytes())?;
+    let round1_messages = (1..=participant.max_signers)
+        .filter(|&sender| sender != index)
+        .map(|sender| read_file(&out_dir.join(dkg_round1_file(sender))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let outgoing = participant.receive_round1(&round1_messages)?;
+    // Shares are a deterministic function of the saved polynomial, so if saving the new
+    // state fails after this, rerunning dkg-round2 rewrites the same files
+    for (&recipient, message) in &outgoing {
+        let path = out_dir.join(dkg_round2_file(index, recipient));
+        keystore::write_private_file(&path, message)?;
+        println!("Wrote {}", path.display());
+    }
+    keystore::save_dkg_state(&state_path, &participant, password.as_bytes())?;
+    println!(
+        "Hand each dkg-round2-{}-to-<j>.bin only to party j, put the files addressed to {} in {}, then run dkg-finish",
+        index,
+        index,
+        out_dir.display()
+    );
+    Ok(())
+}
+
+/// Last DKG step of party `--index`: derive its own share and the group's public key package
+fn dkg_finish(mut args: Args) -> Result<(), Failure> {
+    let index: u16 = args.parsed("--index")?.ok_or(UsageError("Missing --index".to_string()))?;
+    let out_dir = PathBuf::from(args.value("--out-dir")?.unwrap_or_else(|| ".".to_string()));
+    let group_out = args.value("--group-out")?.map(PathBuf::from).unwrap_or_else(|| out_dir.join("group.pub"));
+    let password = read_password(&mut args)?;
+    args.finish()?;
+
+    let state_path = dkg_state_path(&out_dir, index);
+    let key_path = out_dir.join(format!("signer-{}.key", index));
+    refuse_existing([&key_path, &group_out])?;
+
+    let mut participant = keystore::load_dkg_state::<Suite, _>(&state_path, password.as_bytes())?;
+    let round2_messages = (1..=participant.max_signers)
+        .filter(|&sender| sender != index)
+        .map(|sender| read_file(&out_dir.join(dkg_round2_file(sender, index))))
+        .collect::<Result<Vec<_>, _>>()?;
+    participant.receive_round2(&round2_messages)?;
+    let (key_package, pubkey_package) = participant.finish()?;
+    let threshold = *key_package.min_signers();
+
+    keystore::save_key_package(&key_path, &ThresholdSigner::new(index, key_package), password.as_bytes())?;
+    println!("Wrote {}", key_path.display());
+    keystore::save_public_key_package(&group_out, &pubkey_package)?;
+    println!("Wrote {}", group_out.display());
+    std::fs::remove_file(&state_path)
+        .map_err(|e| Failure::Failed(format!("Failed to delete {}: {}", state_path.display(), e)))?;
+
+    println!(
+        "Joined a {}-of-{} group (DKG) as signer {}, group key {}",
+        threshold,
+        participant.max_signers,
+        index,
+        hex::encode(verifying_key_bytes(pubkey_package.verifying_key())?)
+    );
+    Ok(())
+}
+
+fn dkg_state_path(out_dir: &Path, index: u16) -> PathBuf {
+    out_dir.join(format!("dkg-{}.state", index))
+}
+
+fn dkg_round1_file(sender: u16) -> String {
+    format!("dkg-round1-{}.bin", sender)
+}
+
+fn dkg_round2_file(sender: u16, recipient: u16) -> String {
+    format!("dkg-round2-{}-to-{}.bin", sender, recipient)
+}
+
+/// Refuse to replace any of `paths`: each may hold the only copy of a secret
+fn refuse_existing<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> Result<(), Failure> {
+    match paths.into_iter().find(|path| path.exists()) {
+        Some(existing) => Err(Failure::Failed(format!("{} already exists", existing.display()))),
+        None => Ok(()),
+    }
+}
+
+fn sign(mut args: Args) -> Result<(), Failure> {
+    let pubkey_package = keystore::load_public_key_package::<Suite, _>(args.required("--group")?)?;
+    let key_files = args.values("--key");
+    let remote_signers = args.values("--signer");
+    let threshold: Option<u16> = args.parsed("--threshold")?;
+    let participants = args.value("--participants")?.map(|list| parse_indices(&list)).transpose()?;
+    let path: Option<DerivationPath> = args.parsed("--path")?;
+    let out = args.value("--out")?;
+    if key_files.is_empty() == remote_signers.is_empty() {
+        return Err(Failure::Usage("Give either --key files or --signer addresses".to_string()));
+    }
+    let message = read_message(&mut args, true)?.unwrap_or_default();
+
+    let mut endpoints: Vec<Box<dyn SignerTransport>> = Vec::new();
+    let mut min_signers = None;
+    if remote_signers.is_empty() {
+        let password = read_password(&mut args)?;
+        args.finish()?;
+        for file in &key_files {
+            let signer = keystore::load_signer::<Suite, _>(file, password.as_bytes())?;
+            if signer.key_package.verifying_key() != pubkey_package.verifying_key() {
+                return Err(Failure::Failed(format!("{} belongs to another group", file)));
+            }
+            min_signers = Some(*signer.key_package.min_signers());
+            endpoints.push(Box::new(InMemoryTransport::new(signer)));
+        }
+    } else {
+        args.finish()?;
+        for signer in &remote_signers {
+            let (index, addr) = signer
+                .split_once('=')
+                .and_then(|(index, addr)| Some((index.parse::<u16>().ok()?, addr)))
+                .ok_or_else(|| UsageError(format!("Invalid --signer {:?}, expected <index>=<host:port>", signer)))?;
+            endpoints.push(Box::new(TcpTransport::<Suite>::connect(index, addr)?));
+        }
+    }
+
+    // Local key packages know the threshold; remote signers don't tell
+    let threshold = threshold
+        .or(min_signers)
+        .ok_or(UsageError("Missing --threshold (required with --signer)".to_string()))?;
+    let mut coordinator = PublicCoordinator::new(threshold, pubkey_package);
+    for endpoint in endpoints {
+        coordinator.add_signer(endpoint)?;
+    }
+    let participants = participants.unwrap_or_else(|| coordinator.signer_indices());
+
+    let signature = match &path {
+        Some(path) => coordinator.perform_threshold_signing_at(path, &message, participants.clone())?,
+        None => coordinator.perform_threshold_signing(&message, participants.clone())?,
+    };
+    eprintln!(
+        "Signed {} bytes with signers {:?} under public key {}",
+        message.len(),
+        participants,
+        hex::encode(&signature.public_key)
+    );
+
+    let encoded = encode::<Suite, _>(&signature);
+    match out {
+        Some(out) => write_file(Path::new(&out), &encoded)?,
+        None => println!("{}", hex::encode(encoded)),
+    }
+    Ok(())
+}
+
+fn verify(mut args: Args) -> Result<(), Failure> {
+    let signature = read_signature(&args.required("--signature")?)?;
+    let group = args.value("--group")?;
+    let path: Option<DerivationPath> = args.parsed("--path")?;
+    let message = read_message(&mut args, true)?.unwrap_or_default();
+    args.finish()?;
+
+    if let Some(group) = group {
+        let pubkey_package = keystore::load_public_key_package::<Suite, _>(group)?;
+        let root = ExtendedVerifyingKey::from_group(&pubkey_package)?;
+        let expected = root.derive(&path.unwrap_or_default())?;
+        if signature.public_key != verifying_key_bytes(expected.verifying_key())? {
+            return Err(Failure::Failed(format!(
+                "Signature is for public key {}, not the expected {}",
+                hex::encode(&signature.public_key),
+                hex::encode(verifying_key_bytes(expected.verifying_key())?)
+            )));
+        }
+    } else if path.is_some() {
+        return Err(Failure::Usage("--path needs --group".to_string()));
+    }
+
+    if !signature.verify::<Suite>(&message) {
+        return Err(Failure::Failed("Signature is INVALID".to_string()));
+    }
+    println!("Signature is valid under public key {}", hex::encode(signature.verifying_key()));
+    Ok(())
+}
+
+fn inspect(mut args: Args) -> Result<(), Failure> {
+    let files = args.positional();
+    args.finish()?;
+    if files.is_empty() {
+        return Err(Failure::Usage("Missing file to inspect".to_string()));
+    }
+
+    for file in &files {
+        let bytes = read_file(Path::new(file))?;
+        println!("{}:", file);
+        if bytes.starts_with(&KEYSTORE_MAGIC) {
+            inspect_keystore(&bytes)?;
+        } else {
+            inspect_signature(file)?;
+        }
+    }
+    Ok(())
+}
+
+fn inspect_keystore(bytes: &[u8]) -> Result<(), Failure> {
+    let info = keystore::inspect(bytes)?;
+    match info.kind {
+        KeystoreKind::EncryptedKeyPackage => {
+            println!("  encrypted key package of signer {}", info.signer_index.unwrap_or_default());
+            println!("  ciphersuite: {}", info.ciphersuite);
+        }
+        KeystoreKind::EncryptedDkgState => {
+            println!("  encrypted DKG state of participant {}", info.signer_index.unwrap_or_default());
+            println!("  ciphersuite: {}", info.ciphersuite);
+        }
+        KeystoreKind::PublicKeyPackage => {
+            println!("  public key package");
+            println!("  ciphersuite: {}", info.ciphersuite);
+            if info.ciphersuite == <Suite as frost::Ciphersuite>::ID {
+                let pubkey_package = keystore::decode_public_key_package::<Suite>(bytes)?;
+                println!("  group key: {}", hex::encode(verifying_key_bytes(pubkey_package.verifying_key())?));
+                println!("  signers: {}", pubkey_package.verifying_shares().len());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn inspect_signature(file: &str) -> Result<(), Failure> {
+    let signature = read_signature(file)?;
+    println!("  signature ({})", <Suite as frost::Ciphersuite>::ID);
+    println!("  signature bytes: {}", hex::encode(&signature.signature));
+    println!("  public key: {}", hex::encode(&signature.public_key));
+    if let Some(randomized) = &signature.randomized_public_key {
+        println!("  randomized public key: {}", hex::encode(randomized));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "prove")]
+fn prove(mut args: Args) -> Result<(), Failure> {
+    let signature_file = args.required("--signature")?;
+    let proof_path = PathBuf::from(args.value("--out")?.unwrap_or_else(|| "proof.bin".to_string()));
+    let vk_path = PathBuf::from(args.value("--vk-out")?.unwrap_or_else(|| "vk.bin".to_string()));
+    let message = read_message(&mut args, true)?.unwrap_or_default();
+    args.finish()?;
+
+    let encoded = encode::<Suite, _>(&read_signature(&signature_file)?);
+    eprintln!("Proving (this may take a few minutes)...");
+    let outputs = prove::prove(&message, &encoded, &proof_path, &vk_path).map_err(Failure::Failed)?;
+    println!("Wrote {} and {}", proof_path.display(), vk_path.display());
+    print_proof_outputs(&outputs);
+    Ok(())
+}
+
+#[cfg(feature = "prove")]
+fn verify_proof(mut args: Args) -> Result<(), Failure> {
+    let proof_path = PathBuf::from(args.required("--proof")?);
+    let vk_path = PathBuf::from(args.required("--vk")?);
+    let message = read_message(&mut args, false)?;
+    args.finish()?;
+
+    let outputs = prove::verify(&proof_path, &vk_path).map_err(Failure::Failed)?;
+    print_proof_outputs(&outputs);
+    if message.is_some_and(|message| message != outputs.message) {
+        return Err(Failure::Failed("Proof is for another message".to_string()));
+    }
+    if !outputs.is_valid {
+        return Err(Failure::Failed("Proof is valid, but attests an INVALID signature".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "prove")]
+fn print_proof_outputs(outputs: &prove::ProofOutputs) {
+    println!("Signature valid in zkVM: {}", outputs.is_valid);
+    println!("Public key: {}", hex::encode(outputs.public_key));
+    if let Some(randomized) = outputs.randomized_public_key {
+        println!("Randomized public key: {}", hex::encode(randomized));
+    }
+    println!("Message: {}", hex::encode(&outputs.message));
+}
+
+#[cfg(not(feature = "prove"))]
+fn prove(_: Args) -> Result<(), Failure> {
+    Err(Failure::Failed("threshold-cli was built without the `prove` feature".to_string()))
+}
+
+#[cfg(not(feature = "prove"))]
+fn verify_proof(_: Args) -> Result<(), Failure> {
+    Err(Failure::Failed("threshold-cli was built without the `prove` feature".to_string()))
+}
+
+/// The message from --message-file or --message-hex, or from stdin if `stdin_default`
+fn read_message(args: &mut Args, stdin_default: bool) -> Result<Option<Vec<u8>>, Failure> {
+    match (args.value("--message-file")?, args.value("--message-hex")?) {
+        (Some(_), Some(_)) => Err(Failure::Usage("Give --message-file or --message-hex, not both".to_string())),
+        (Some(path), None) if path == "-" => read_stdin().map(Some),
+        (Some(path), None) => read_file(Path::new(&path)).map(Some),
+        (None, Some(hex)) => hex::decode(hex.trim())
+            .map(Some)
+            .map_err(|e| Failure::Usage(format!("Invalid --message-hex: {}", e))),
+        (None, None) if stdin_default => read_stdin().map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Read a signature written by `sign`, as a binary envelope or as hex text
+fn read_signature(file: &str) -> Result<CombinedSignature, Failure> {
+    let bytes = read_file(Path::new(file))?;
+    if bytes.starts_with(&ENVELOPE_MAGIC) {
+        return Ok(decode::<Suite, _>(&bytes)?);
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let bytes = hex::decode(text.trim())
+        .map_err(|_| Failure::Failed(format!("{} is neither a signature envelope nor its hex encoding", file)))?;
+    Ok(decode::<Suite, _>(&bytes)?)
+}
+
+/// Keystore password from --password-file, or else the environment
+fn read_password(args: &mut Args) -> Result<String, Failure> {
+    match args.value("--password-file")? {
+        Some(path) => Ok(std::fs::read_to_string(&path)
+            .map_err(|e| Failure::Failed(format!("Failed to read password file {}: {}", path, e)))?
+            .trim_end_matches(['\r', '\n'])
+            .to_string()),
+        None => std::env::var(PASSWORD_ENV)
+            .map_err(|_| Failure::Failed(format!("No --password-file given and {} is not set", PASSWORD_ENV))),
+    }
+}
+
+/// Parse a comma-separated list of signer indices
+fn parse_indices(list: &str) -> Result<Vec<u16>, UsageError> {
+    list.split(',')
+        .map(|index| index.trim().parse::<u16>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| UsageError(format!("Invalid --participants {:?}, expected e.g. 1,3,5", list)))
+}
+
+fn verifying_key_bytes(verifying_key: &frost::VerifyingKey) -> Result<Vec<u8>, Failure> {
+    verifying_key
+        .serialize()
+        .map_err(|e| Failure::Failed(format!("Failed to serialize verifying key: {}", e)))
+}
+
+fn read_stdin() -> Result<Vec<u8>, Failure> {
+    let mut message = Vec::new();
+    io::stdin()
+        .read_to_end(&mut message)
+        .map_err(|e| Failure::Failed(format!("Failed to read stdin: {}", e)))?;
+    Ok(message)
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, Failure> {
+    std::fs::read(path).map_err(|e| Failure::Failed(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), Failure> {
+    std::fs::File::create(path)
+        .and_then(|mut file| file.write_all(bytes))
+        .map_err(|e| Failure::Failed(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(command: fn(Args) -> Result<(), Failure>, argv: &[&str]) {
+        let args = Args::parse(argv.iter().map(|arg| arg.to_string())).unwrap_or_else(|e| panic!("{}", e.0));
+        if let Err(Failure::Usage(e) | Failure::Failed(e)) = command(args) {
+            panic!("{:?} failed: {}", argv, e);
+        }
+    }
+
+    /// Copy `file(sender, recipient)` from every party's directory to every other party's
+    fn deliver(party_dirs: &[PathBuf], file: impl Fn(u16, u16) -> String) {
+        for (sender, from) in (1..).zip(party_dirs) {
+            for (recipient, to) in (1..).zip(party_dirs) {
+                if sender != recipient {
+                    let name = file(sender, recipient);
+                    std::fs::copy(from.join(&name), to.join(&name)).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_keygen_dkg_steps_produce_signing_keys() {
+        let dir = std::env::temp_dir().join(format!("cli-dkg-test-{}", process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let password_file = dir.join("password");
+        std::fs::write(&password_file, "pw\n").unwrap();
+        let password_file = password_file.to_str().unwrap();
+        let party_dirs: Vec<PathBuf> = (1..=3).map(|index| dir.join(format!("party-{}", index))).collect();
+
+        // Every party only ever touches its own directory; files move between them by copy
+        let step = |name: &str, index: u16, party_dir: &Path, extra: &[&str]| {
+            let index = index.to_string();
+            let mut argv = vec![name, "--index", &index, "--out-dir", party_dir.to_str().unwrap()];
+            argv.extend_from_slice(&["--password-file", password_file]);
+            argv.extend_from_slice(extra);
+            run(keygen, &argv);
+        };
+        for (index, party_dir) in (1..).zip(&party_dirs) {
+            step("dkg-round1", index, party_dir, &["--threshold", "2", "--participants", "3"]);
+        }
+        deliver(&party_dirs, |sender, _| dkg_round1_file(sender));
+        for (index, party_dir) in (1..).zip(&party_dirs) {
+            step("dkg-round2", index, party_dir, &[]);
+        }
+        deliver(&party_dirs, dkg_round2_file);
+        for (index, party_dir) in (1..).zip(&party_dirs) {
+            step("dkg-finish", index, party_dir, &[]);
+            assert!(!dkg_state_path(party_dir, index).exists());
+        }
+
+        let pubkey_packages: Vec<_> = party_dirs
+            .iter()
+            .map(|party_dir| keystore::load_public_key_package::<Suite, _>(party_dir.join("group.pub")).unwrap())
+            .collect();
+        assert!(pubkey_packages.windows(2).all(|w| w[0] == w[1]));
+
+        let mut coordinator = PublicCoordinator::new(2, pubkey_packages[0].clone());
+        for (index, party_dir) in (1..).zip(&party_dirs) {
+            let key_path = party_dir.join(format!("signer-{}.key", index));
+            let signer = keystore::load_signer::<Suite, _>(key_path, b"pw").unwrap();
+            coordinator.add_signer(Box::new(InMemoryTransport::new(signer))).unwrap();
+        }
+        let signature = coordinator.perform_threshold_signing(b"dkg message", vec![1, 3]).unwrap();
+        assert!(signature.verify::<Suite>(b"dkg message"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,64 @@
+use sp1_sdk::{ProverClient, SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey};
+use std::path::Path;
+
+/// The guest program that verifies a `CombinedSignature` (see `program/`)
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+/// Public values committed by the guest program, in commit order
+pub struct ProofOutputs {
+    pub is_valid: bool,
+    /// Group key of the signature
+    pub public_key: [u8; 32],
+    /// One-time key a rerandomized signature verifies under; committed as all zero when absent
+    pub randomized_public_key: Option<[u8; 32]>,
+    pub message: Vec<u8>,
+}
+
+/// Prove that the guest program checked `encoded_signature` on `message`, and
+/// write the proof and verifying key for `verify` and on-chain verification
+pub fn prove(message: &[u8], encoded_signature: &[u8], proof_path: &Path, vk_path: &Path) -> Result<ProofOutputs, String> {
+    let client = ProverClient::new();
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&message.to_vec());
+    stdin.write(&encoded_signature.to_vec());
+
+    let (pk, vk) = client.setup(ELF);
+    let proof = client
+        .prove(&pk, stdin)
+        .run()
+        .map_err(|e| format!("Proving failed: {}", e))?;
+
+    write(proof_path, &bincode::serialize(&proof).map_err(|e| e.to_string())?)?;
+    write(vk_path, &bincode::serialize(&vk).map_err(|e| e.to_string())?)?;
+    Ok(outputs(proof))
+}
+
+/// Check a proof written by `prove` and return its public values
+pub fn verify(proof_path: &Path, vk_path: &Path) -> Result<ProofOutputs, String> {
+    let proof: SP1ProofWithPublicValues = bincode::deserialize(&read(proof_path)?)
+        .map_err(|e| format!("Invalid proof file {}: {}", proof_path.display(), e))?;
+    let vk: SP1VerifyingKey = bincode::deserialize(&read(vk_path)?)
+        .map_err(|e| format!("Invalid verifying key file {}: {}", vk_path.display(), e))?;
+
+    ProverClient::new()
+        .verify(&proof, &vk)
+        .map_err(|e| format!("Proof verification failed: {}", e))?;
+    Ok(outputs(proof))
+}
+
+fn outputs(mut proof: SP1ProofWithPublicValues) -> ProofOutputs {
+    ProofOutputs {
+        is_valid: proof.public_values.read(),
+        public_key: proof.public_values.read(),
+        randomized_public_key: Some(proof.public_values.read::<[u8; 32]>()).filter(|key| *key != [0u8; 32]),
+        message: proof.public_values.read(),
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    std::fs::write(path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -1,5 +1,6 @@
 use sp1_sdk::{ProverClient, SP1Stdin};
-use threshold_signing_lib::{ThresholdSigner, ThresholdCoordinator, generate_frost_keys, serialize};
+use threshold_signing_lib::ciphersuite::Ed25519Sha512;
+use threshold_signing_lib::{ThresholdSigner, ThresholdCoordinator, generate_frost_keys, encode};
 
 fn main() {
     println!("=== Threshold Signature SP1 zkVM Demo ===\n");
@@ -15,12 +16,12 @@ fn main() {
 
     // Step 2: Generate FROST keys using distributed key generation
     println!("Generating FROST threshold keys...");
-    let (key_packages, pubkey_package) = generate_frost_keys(total_signers, threshold)
+    let (key_packages, pubkey_package) = generate_frost_keys::<Ed25519Sha512>(total_signers, threshold)
         .expect("Failed to generate FROST keys");
     println!("Keys generated successfully\n");
 
     // Create signers from key packages
-    let signers: Vec<ThresholdSigner> = key_packages
+    let signers: Vec<ThresholdSigner<Ed25519Sha512>> = key_packages
         .into_iter()
         .enumerate()
         .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
@@ -44,9 +45,12 @@ fn main() {
     println!("Verifying signature locally...");
     use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 
-    let verifying_key = VerifyingKey::from_bytes(&combined_signature.public_key)
+    let public_key: &[u8; 32] = combined_signature.verifying_key().try_into()
+        .expect("Invalid public key length");
+    let verifying_key = VerifyingKey::from_bytes(public_key)
         .expect("Invalid public key");
-    let signature = Signature::from_bytes(&combined_signature.signature);
+    let signature = Signature::from_slice(&combined_signature.signature)
+        .expect("Invalid signature length");
 
     verifying_key
         .verify(message, &signature)
@@ -54,7 +58,7 @@ fn main() {
     println!("Local verification successful\n");
 
     // Step 4: Serialize for zkVM
-    let combined_sig_serialized = serialize(&combined_signature);
+    let combined_sig_serialized = encode::<Ed25519Sha512, _>(&combined_signature);
 
     // Step 5: Generate zkVM proof
     println!("Generating SP1 proof...");
@@ -79,12 +83,16 @@ fn main() {
 
     // Step 7: Extract public outputs
     let is_valid = proof.public_values.read::<bool>();
-    let public_key = proof.public_values.read::<[u8; 32]>();
+    let group_key = proof.public_values.read::<[u8; 32]>();
+    let randomized_key = proof.public_values.read::<[u8; 32]>();
     let message_out = proof.public_values.read::<Vec<u8>>();
 
     println!("=== Results ===");
     println!("Signature valid in zkVM: {}", is_valid);
-    println!("Public key: {}", hex::encode(public_key));
+    println!("Group key: {}", hex::encode(group_key));
+    if randomized_key != [0u8; 32] {
+        println!("Randomized key: {}", hex::encode(randomized_key));
+    }
     println!("Message: {:?}", String::from_utf8_lossy(&message_out));
 
     // Step 8: Save proof for Solidity verification
@@ -0,0 +1,495 @@
+//! Async coordinator, transports and signer loop on tokio
+//!
+//! Only plain two-round signing is async: `AsyncCoordinator` runs round 1 and
+//! round 2 against every signer at once. Randomized, derived-key, preprocessed
+//! (`CommitmentPool`) and batch signing stay sync-only, through
+//! `PublicCoordinator` and `SignerTransport`. On the signer side the async
+//! `serve_signer` answers every request kind, since each one is handed to
+//! `ThresholdSigner` as it is by the blocking loop.
+
+use async_trait::async_trait;
+use frost_core::{self as frost, Ciphersuite};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::borrow::BorrowMut;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::error::{ThresholdError, codec, identifier};
+use crate::serialization::{CombinedSignature, SignerMessage, SignerResponse, decode, encode};
+use crate::session::{DEFAULT_SESSION_TIMEOUT, SigningSession};
+use crate::threshold::{ThresholdSigner, check_distinct_signers, unexpected_response};
+use crate::transport::{DEFAULT_TCP_TIMEOUT, InMemoryTransport, MAX_FRAME_SIZE, SignerTransport, transport_error};
+
+/// Default time `AsyncCoordinator` gives each signer to answer one request
+pub const DEFAULT_SIGNER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Async counterpart of `SignerTransport`
+///
+/// Each call sends one request and waits for its response, so the coordinator
+/// can have a request in flight to every signer at once. A call may be dropped
+/// before it completes (timed out, or no longer needed); the transport must stay
+/// usable for the next call.
+#[async_trait]
+pub trait AsyncSignerTransport: Send {
+    /// Identifier (1-based index) of the signer at the other end
+    fn signer_index(&self) -> u16;
+
+    /// Round 1: request fresh nonce commitments
+    async fn request_commitments(&mut self) -> Result<SignerResponse, ThresholdError>;
+
+    /// Round 2: deliver the serialized `frost::SigningPackage` and return the
+    /// signer's response
+    async fn request_share(&mut self, signing_package: &[u8]) -> Result<SignerResponse, ThresholdError>;
+}
+
+#[async_trait]
+impl<C, S> AsyncSignerTransport for InMemoryTransport<C, S>
+where
+    C: Ciphersuite + Send,
+    S: BorrowMut<ThresholdSigner<C>> + Send,
+{
+    fn signer_index(&self) -> u16 {
+        SignerTransport::signer_index(self)
+    }
+
+    async fn request_commitments(&mut self) -> Result<SignerResponse, ThresholdError> {
+        SignerTransport::send_round1_request(self)
+    }
+
+    async fn request_share(&mut self, signing_package: &[u8]) -> Result<SignerResponse, ThresholdError> {
+        SignerTransport::send_signing_package(self, signing_package)
+    }
+}
+
+/// Transport to a signer reachable over TCP, on tokio
+///
+/// Speaks the same framed protocol as `TcpTransport`, so the remote end can run
+/// either `transport::serve_signer` or `async_signing::serve_signer`.
+pub struct AsyncTcpTransport<C: Ciphersuite> {
+    signer_index: u16,
+    addr: SocketAddr,
+    stream: Option<TcpStream>,
+    ciphersuite: PhantomData<C>,
+}
+
+impl<C: Ciphersuite> AsyncTcpTransport<C> {
+    /// Connect to the signer with the given index at `addr`
+    pub async fn connect(signer_index: u16, addr: SocketAddr) -> Result<Self, ThresholdError> {
+        let stream = open_stream(signer_index, addr).await?;
+        Ok(Self {
+            signer_index,
+            addr,
+            stream: Some(stream),
+            ciphersuite: PhantomData,
+        })
+    }
+
+    /// Send one request and read its response
+    ///
+    /// The stream is taken out for the whole exchange. If the call is dropped
+    /// halfway, the connection is dropped with it and the next call reconnects,
+    /// so a late response is never read as the answer to a later request.
+    async fn call(&mut self, request: &SignerMessage) -> Result<Vec<u8>, ThresholdError> {
+        let mut stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => open_stream(self.signer_index, self.addr).await?,
+        };
+        write_frame(&mut stream, &encode::<C, _>(request))
+            .await
+            .map_err(|e| transport_error(self.signer_index, "send", e))?;
+        let response = read_frame(&mut stream)
+            .await
+            .map_err(|e| transport_error(self.signer_index, "receive", e))?;
+
+        self.stream = Some(stream);
+        Ok(response)
+    }
+}
+
+async fn open_stream(signer_index: u16, addr: SocketAddr) -> Result<TcpStream, ThresholdError> {
+    let stream = tokio::time::timeout(DEFAULT_TCP_TIMEOUT, TcpStream::connect(addr))
+        .await
+        .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+        .map_err(|e| transport_error(signer_index, "connect", e))?;
+    stream.set_nodelay(true).map_err(|e| transport_error(signer_index, "configure", e))?;
+    Ok(stream)
+}
+
+#[async_trait]
+impl<C: Ciphersuite + Send> AsyncSignerTransport for AsyncTcpTransport<C> {
+    fn signer_index(&self) -> u16 {
+        self.signer_index
+    }
+
+    async fn request_commitments(&mut self) -> Result<SignerResponse, ThresholdError> {
+        let response = self.call(&SignerMessage::Round1Request {
+            signer_index: self.signer_index,
+        }).await?;
+        decode::<C, _>(&response)
+    }
+
+    async fn request_share(&mut self, signing_package: &[u8]) -> Result<SignerResponse, ThresholdError> {
+        let response = self.call(&SignerMessage::Round2Request {
+            signer_index: self.signer_index,
+            signing_package: signing_package.to_vec(),
+        }).await?;
+        decode::<C, _>(&response)
+    }
+}
+
+/// Coordinator that talks to all its signers concurrently
+///
+/// Holds only public data, like `PublicCoordinator`. Each round goes out to every
+/// signer at once and each request gets `signer_timeout`, so a slow or unreachable
+/// signer costs at most that long instead of holding up everyone else.
+///
+/// Round 1 is sent to every requested signer and the first `threshold` to answer
+/// become the signing set; the others are no longer waited for. Round 2 needs
+/// every member of that set, so a signer that answers round 1 and then stalls
+/// fails the signing with `ThresholdError::Timeout` naming it.
+pub struct AsyncCoordinator<C: Ciphersuite> {
+    pub threshold: u16,
+    pub pubkey_package: frost::keys::PublicKeyPackage<C>,
+    pub signer_timeout: Duration,
+    endpoints: BTreeMap<u16, Box<dyn AsyncSignerTransport>>,
+}
+
+impl<C: Ciphersuite> AsyncCoordinator<C> {
+    pub fn new(threshold: u16, pubkey_package: frost::keys::PublicKeyPackage<C>) -> Self {
+        Self {
+            threshold,
+            pubkey_package,
+            signer_timeout: DEFAULT_SIGNER_TIMEOUT,
+            endpoints: BTreeMap::new(),
+        }
+    }
+
+    /// Register the endpoint for one signer (see `PublicCoordinator::add_signer`)
+    pub fn add_signer(&mut self, transport: Box<dyn AsyncSignerTransport>) -> Result<(), ThresholdError> {
+        let index = transport.signer_index();
+        if !self.pubkey_package.verifying_shares().contains_key(&identifier::<C>(index)?) {
+            return Err(ThresholdError::UnknownSigner(index));
+        }
+        if self.endpoints.contains_key(&index) {
+            return Err(ThresholdError::InvalidParameters(format!("Signer {} is already registered", index)));
+        }
+
+        self.endpoints.insert(index, transport);
+        Ok(())
+    }
+
+    /// Indices of all registered signers
+    pub fn signer_indices(&self) -> Vec<u16> {
+        self.endpoints.keys().copied().collect()
+    }
+
+    /// Sign `message` with the first `threshold` of `signer_indices` to answer
+    pub async fn perform_threshold_signing(
+        &mut self,
+        message: &[u8],
+        signer_indices: Vec<u16>,
+    ) -> Result<CombinedSignature, ThresholdError> {
+        check_distinct_signers(&signer_indices)?;
+        if let Some(unknown) = signer_indices.iter().find(|idx| !self.endpoints.contains_key(idx)) {
+            return Err(ThresholdError::UnknownSigner(*unknown));
+        }
+        let threshold = self.threshold as usize;
+        let timeout = self.signer_timeout;
+
+        // Round 1: ask everyone, keep the first `threshold` valid answers
+        let mut round1: FuturesUnordered<_> = self.endpoints
+            .iter_mut()
+            .filter(|(idx, _)| signer_indices.contains(idx))
+            .map(|(&idx, transport)| async move {
+                let response = with_timeout(idx, timeout, transport.request_commitments()).await;
+                (idx, response.and_then(|response| parse_commitments(idx, response)))
+            })
+            .collect();
+        if round1.len() < threshold {
+            return Err(ThresholdError::InsufficientSigners {
+                required: threshold,
+                available: round1.len(),
+            });
+        }
+
+        let mut commitments = BTreeMap::new();
+        let mut failures = Vec::new();
+        while commitments.len() < threshold {
+            match round1.next().await {
+                Some((idx, Ok(commitment))) => {
+                    commitments.insert(idx, commitment);
+                }
+                Some((idx, Err(e))) => failures.push((idx, e)),
+                None => return Err(round1_failure(threshold, commitments.len(), failures)),
+            }
+        }
+        // Stop waiting for the slower signers
+        drop(round1);
+
+        let participants: Vec<u16> = commitments.keys().copied().collect();
+        let mut session = SigningSession::new(
+            self.threshold,
+            self.pubkey_package.clone(),
+            message,
+            &participants,
+            DEFAULT_SESSION_TIMEOUT,
+        )?;
+        for (idx, commitment) in commitments {
+            session.add_commitments(idx, commitment)?;
+        }
+        let signing_package = session.signing_package().map(<[u8]>::to_vec).unwrap_or_default();
+
+        // Round 2: every member of the signing set must answer
+        let mut round2: FuturesUnordered<_> = self.endpoints
+            .iter_mut()
+            .filter(|(idx, _)| participants.contains(idx))
+            .map(|(&idx, transport)| {
+                let signing_package = &signing_package;
+                async move { (idx, with_timeout(idx, timeout, transport.request_share(signing_package)).await) }
+            })
+            .collect();
+
+        let mut stragglers = Vec::new();
+        while let Some((idx, response)) = round2.next().await {
+            match response {
+                Ok(response) => session.receive_share(idx, &encode::<C, _>(&response))?,
+                Err(ThresholdError::Timeout { .. }) => stragglers.push(idx),
+                Err(e) => return Err(e),
+            }
+        }
+        if !stragglers.is_empty() {
+            stragglers.sort_unstable();
+            return Err(ThresholdError::Timeout { stragglers });
+        }
+
+        session.finish()
+    }
+}
+
+/// Run one request to `signer_index`, giving up after `timeout`
+async fn with_timeout<T>(
+    signer_index: u16,
+    timeout: Duration,
+    request: impl Future<Output = Result<T, ThresholdError>>,
+) -> Result<T, ThresholdError> {
+    tokio::time::timeout(timeout, request)
+        .await
+        .unwrap_or_else(|_| Err(ThresholdError::Timeout { stragglers: vec![signer_index] }))
+}
+
+fn parse_commitments<C: Ciphersuite>(
+    signer_index: u16,
+    response: SignerResponse,
+) -> Result<frost::round1::SigningCommitments<C>, ThresholdError> {
+    match response {
+        SignerResponse::Commitments { signer_index: from, commitments } if from == signer_index => {
+            frost::round1::SigningCommitments::<C>::deserialize(&commitments).map_err(codec("Invalid commitments"))
+        }
+        other => Err(unexpected_response(signer_index, other)),
+    }
+}
+
+/// Why round 1 ended with fewer than `threshold` commitments: the signers that
+/// timed out if there were any, otherwise the first refusal or failure
+fn round1_failure(threshold: usize, available: usize, failures: Vec<(u16, ThresholdError)>) -> ThresholdError {
+    let mut stragglers: Vec<u16> = failures
+        .iter()
+        .filter(|(_, e)| matches!(e, ThresholdError::Timeout { .. }))
+        .map(|(idx, _)| *idx)
+        .collect();
+    if !stragglers.is_empty() {
+        stragglers.sort_unstable();
+        return ThresholdError::Timeout { stragglers };
+    }
+    failures
+        .into_iter()
+        .map(|(_, e)| e)
+        .next()
+        .unwrap_or(ThresholdError::InsufficientSigners { required: threshold, available })
+}
+
+/// Write one frame (see `transport::write_frame`)
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, payload: &[u8]) -> io::Result<()> {
+    if payload.len() > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Frame too large"));
+    }
+    writer.write_all(&(payload.len() as u32).to_be_bytes()).await?;
+    writer.write_all(payload).await?;
+    writer.flush().await
+}
+
+/// Read one frame, rejecting frames over `MAX_FRAME_SIZE` (see `transport::read_frame`)
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut len_bytes = [0u8; 4];
+    reader.read_exact(&mut len_bytes).await?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Frame too large"));
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await?;
+    Ok(payload)
+}
+
+/// Answer protocol requests arriving on `stream` until the peer disconnects
+/// (async counterpart of `transport::serve_signer`)
+pub async fn serve_signer<C: Ciphersuite, S: AsyncRead + AsyncWrite + Unpin>(
+    signer: &mut ThresholdSigner<C>,
+    mut stream: S,
+) -> io::Result<()> {
+    loop {
+        let request = match read_frame(&mut stream).await {
+            Ok(request) => request,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let response = signer.receive_serialized_signing_request(&request);
+        write_frame(&mut stream, &response).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::threshold::generate_frost_keys;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Instant;
+
+    /// In-memory signer that sleeps before answering round 1
+    struct SlowTransport {
+        inner: InMemoryTransport<E>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl AsyncSignerTransport for SlowTransport {
+        fn signer_index(&self) -> u16 {
+            AsyncSignerTransport::signer_index(&self.inner)
+        }
+
+        async fn request_commitments(&mut self) -> Result<SignerResponse, ThresholdError> {
+            tokio::time::sleep(self.delay).await;
+            self.inner.request_commitments().await
+        }
+
+        async fn request_share(&mut self, signing_package: &[u8]) -> Result<SignerResponse, ThresholdError> {
+            self.inner.request_share(signing_package).await
+        }
+    }
+
+    /// Coordinator for a 5-of-3 group where the signers in `slow` take `delay` to
+    /// answer round 1
+    fn coordinator(slow: &[u16], delay: Duration) -> AsyncCoordinator<E> {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+        let mut coordinator = AsyncCoordinator::new(3, pubkey_package);
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let index = (i + 1) as u16;
+            let inner = InMemoryTransport::new(ThresholdSigner::new(index, kp));
+            if slow.contains(&index) {
+                coordinator.add_signer(Box::new(SlowTransport { inner, delay })).unwrap();
+            } else {
+                coordinator.add_signer(Box::new(inner)).unwrap();
+            }
+        }
+        coordinator
+    }
+
+    fn assert_valid(signature: &CombinedSignature, message: &[u8]) {
+        let verifying_key = VerifyingKey::from_bytes(signature.public_key.as_slice().try_into().unwrap()).unwrap();
+        assert!(verifying_key.verify(message, &Signature::from_slice(&signature.signature).unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_async_signing_does_not_wait_for_slow_signers() {
+        let mut coordinator = coordinator(&[1, 4], Duration::from_secs(60));
+        let message = b"first responders sign";
+
+        let started = Instant::now();
+        let signature = coordinator
+            .perform_threshold_signing(message, vec![1, 2, 3, 4, 5])
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_valid(&signature, message);
+    }
+
+    #[tokio::test]
+    async fn test_async_signing_times_out_when_too_few_signers_answer() {
+        let mut coordinator = coordinator(&[2, 3], Duration::from_secs(60));
+        coordinator.signer_timeout = Duration::from_millis(100);
+
+        let result = coordinator.perform_threshold_signing(b"message", vec![1, 2, 3, 5]).await;
+        assert_eq!(result.unwrap_err(), ThresholdError::Timeout { stragglers: vec![2, 3] });
+
+        let result = coordinator.perform_threshold_signing(b"message", vec![1, 2]).await;
+        assert_eq!(result.unwrap_err(), ThresholdError::InsufficientSigners { required: 3, available: 2 });
+
+        let result = coordinator.perform_threshold_signing(b"message", vec![1, 6]).await;
+        assert_eq!(result.unwrap_err(), ThresholdError::UnknownSigner(6));
+    }
+
+    #[tokio::test]
+    async fn test_async_signing_rejects_duplicate_signers() {
+        let mut coordinator = coordinator(&[], Duration::ZERO);
+
+        let result = coordinator.perform_threshold_signing(b"message", vec![1, 2, 2]).await;
+        assert!(matches!(result, Err(ThresholdError::InvalidParameters(_))));
+    }
+
+    #[tokio::test]
+    async fn test_async_tcp_transport_reconnects_after_abandoned_request() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut coordinator = AsyncCoordinator::new(2, pubkey_package);
+
+        // Blocking signers; signer 3 takes a second over its first answer and then
+        // serves one connection after another
+        let mut servers = Vec::new();
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let index = (i + 1) as u16;
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            servers.push(thread::spawn(move || {
+                let mut signer = ThresholdSigner::new(index, kp);
+                let mut first = index == 3;
+                for stream in listener.incoming().take(if index == 3 { 2 } else { 1 }) {
+                    let mut stream = stream.unwrap();
+                    while let Ok(request) = crate::transport::read_frame(&mut stream) {
+                        if std::mem::take(&mut first) {
+                            thread::sleep(Duration::from_secs(1));
+                        }
+                        let response = signer.receive_serialized_signing_request(&request);
+                        if crate::transport::write_frame(&mut stream, &response).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }));
+            coordinator
+                .add_signer(Box::new(AsyncTcpTransport::<E>::connect(index, addr).await.unwrap()))
+                .unwrap();
+        }
+
+        // Signer 3 is left out of the first signing and its request abandoned
+        let first = coordinator.perform_threshold_signing(b"first", vec![1, 2, 3]).await.unwrap();
+        assert_valid(&first, b"first");
+
+        // Its late answer must not be taken for the answer to the next request
+        let second = coordinator.perform_threshold_signing(b"second", vec![2, 3]).await.unwrap();
+        assert_valid(&second, b"second");
+
+        drop(coordinator);
+        for server in servers {
+            server.join().unwrap();
+        }
+    }
+}
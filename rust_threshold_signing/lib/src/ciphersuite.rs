@@ -0,0 +1,153 @@
+//! FROST ciphersuites the library can be built with
+//!
+//! Everything in this crate is generic over `C: Ciphersuite`. Each suite below
+//! is behind a cargo feature of the same name; `ed25519` is on by default.
+//! The suite is also recorded in every wire envelope and key file, so data
+//! produced under one suite is rejected by code running another.
+
+pub use frost_core::Ciphersuite;
+
+#[cfg(feature = "ed25519")]
+pub use frost_ed25519::Ed25519Sha512;
+#[cfg(feature = "ed448")]
+pub use frost_ed448::Ed448Shake256;
+#[cfg(feature = "p256")]
+pub use frost_p256::P256Sha256;
+#[cfg(feature = "ristretto255")]
+pub use frost_ristretto255::Ristretto255Sha512;
+#[cfg(feature = "secp256k1")]
+pub use frost_secp256k1::Secp256K1Sha256;
+#[cfg(feature = "secp256k1-tr")]
+pub use frost_secp256k1_tr::Secp256K1Sha256TR;
+
+#[cfg(test)]
+mod tests {
+    // Everything below runs under at least one suite feature
+    #![cfg(any(
+        feature = "ed25519",
+        feature = "ristretto255",
+        feature = "secp256k1",
+        feature = "secp256k1-tr",
+        feature = "p256",
+        feature = "ed448"
+    ))]
+
+    use super::*;
+    use crate::rerandomized::RandomizedCiphersuite;
+    use crate::serialization::{CombinedSignature, decode, encode};
+    use crate::threshold::{
+        ThresholdCoordinator, ThresholdSigner, generate_frost_keys, generate_frost_keys_dkg, repair_frost_share,
+        reshare_frost_keys,
+    };
+
+    /// Sign with dealer and DKG keys under `C` and check the result with `C`'s verifier
+    fn sign_and_verify<C: RandomizedCiphersuite>() {
+        let message = b"ciphersuite round trip";
+        for (key_packages, pubkey_package) in [
+            generate_frost_keys::<C>(4, 3).unwrap(),
+            generate_frost_keys_dkg::<C>(4, 3).unwrap(),
+        ] {
+            let signers = key_packages
+                .into_iter()
+                .enumerate()
+                .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+                .collect();
+            let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+
+            let signature = coordinator.perform_threshold_signing(message, vec![1, 3, 4]).unwrap();
+            assert!(signature.verify::<C>(message));
+            assert!(!signature.verify::<C>(b"another message"));
+
+            // The envelope carries the suite, so it only decodes under `C`
+            let bytes = encode::<C, _>(&signature);
+            let decoded: CombinedSignature = decode::<C, _>(&bytes).unwrap();
+            assert!(decoded.verify::<C>(message));
+
+            let randomized = coordinator.perform_randomized_threshold_signing(message, vec![2, 3, 4], None).unwrap();
+            assert!(randomized.verify::<C>(message));
+
+            let path = "m/3/1".parse().unwrap();
+            let derived = coordinator.perform_threshold_signing_at(&path, message, vec![1, 2, 4]).unwrap();
+            assert!(derived.verify::<C>(message));
+        }
+    }
+
+    /// Reshare and repair do their own group arithmetic, so run them under `C` too
+    fn reshare_and_repair<C: Ciphersuite>() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<C>(3, 2).unwrap();
+        let quorum: Vec<ThresholdSigner<C>> = key_packages
+            .into_iter()
+            .take(2)
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let (new_key_packages, new_pubkey_package) = reshare_frost_keys(&quorum, &pubkey_package, 4, 3).unwrap();
+        assert_eq!(new_pubkey_package.verifying_key(), pubkey_package.verifying_key());
+
+        let helpers: Vec<ThresholdSigner<C>> = [1u16, 3, 4]
+            .iter()
+            .map(|&i| ThresholdSigner::new(i, new_key_packages[i as usize - 1].clone()))
+            .collect();
+        let repaired = repair_frost_share(&helpers, 2, &new_pubkey_package).unwrap();
+        assert_eq!(repaired, new_key_packages[1]);
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn test_ed25519_round_trip() {
+        sign_and_verify::<Ed25519Sha512>();
+        reshare_and_repair::<Ed25519Sha512>();
+    }
+
+    #[cfg(feature = "ristretto255")]
+    #[test]
+    fn test_ristretto255_round_trip() {
+        sign_and_verify::<Ristretto255Sha512>();
+        reshare_and_repair::<Ristretto255Sha512>();
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn test_secp256k1_round_trip() {
+        sign_and_verify::<Secp256K1Sha256>();
+        reshare_and_repair::<Secp256K1Sha256>();
+    }
+
+    #[cfg(feature = "secp256k1-tr")]
+    #[test]
+    fn test_secp256k1_tr_round_trip() {
+        sign_and_verify::<Secp256K1Sha256TR>();
+        reshare_and_repair::<Secp256K1Sha256TR>();
+    }
+
+    #[cfg(feature = "p256")]
+    #[test]
+    fn test_p256_round_trip() {
+        sign_and_verify::<P256Sha256>();
+        reshare_and_repair::<P256Sha256>();
+    }
+
+    #[cfg(feature = "ed448")]
+    #[test]
+    fn test_ed448_round_trip() {
+        sign_and_verify::<Ed448Shake256>();
+        reshare_and_repair::<Ed448Shake256>();
+    }
+
+    #[cfg(all(feature = "ed25519", feature = "secp256k1"))]
+    #[test]
+    fn test_envelope_rejects_other_ciphersuite() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<Ed25519Sha512>(3, 2).unwrap();
+        let signers = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        let mut coordinator = ThresholdCoordinator::new(2, signers, pubkey_package);
+        let signature = coordinator.perform_threshold_signing(b"message", vec![1, 2]).unwrap();
+
+        let bytes = encode::<Ed25519Sha512, _>(&signature);
+        assert!(decode::<Secp256K1Sha256, CombinedSignature>(&bytes).is_err());
+    }
+}
@@ -0,0 +1,622 @@
+use frost_core::{self as frost, Ciphersuite};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::derivation::{DerivationPath, group_chain_code};
+use crate::error::{ThresholdError, codec, identifier};
+use crate::preprocess::CommitmentPool;
+use crate::rerandomized::{RandomizedCiphersuite, Randomizer};
+use crate::serialization::{CombinedSignature, SignerResponse};
+use crate::session::{SessionId, SessionState, SigningSession};
+use crate::threshold::{
+    RobustSigningReport, check_distinct_signers, collect_commitments, combine_serialized_shares, randomize_session,
+    robust_signing, run_round2, session_with_commitments, sign_batch_with_transports, unexpected_response,
+};
+use crate::transport::SignerTransport;
+
+/// Coordinator that holds only public data
+///
+/// Built from the group's `PublicKeyPackage` and one `SignerTransport` per signer.
+/// Unlike `ThresholdCoordinator`, it never owns a `KeyPackage`: secret shares stay
+/// with the signers at the other end of each transport.
+///
+/// After `preprocess`, signing uses the published commitments and needs a single
+/// round trip per signer instead of two.
+pub struct PublicCoordinator<C: Ciphersuite> {
+    pub threshold: u16,
+    pub pubkey_package: frost::keys::PublicKeyPackage<C>,
+    endpoints: BTreeMap<u16, Box<dyn SignerTransport>>,
+    commitment_pool: CommitmentPool<C>,
+    /// Pooled commitments taken by `start_session`, until `release_session`
+    session_commitments: BTreeMap<SessionId, BTreeMap<u16, frost::round1::SigningCommitments<C>>>,
+}
+
+impl<C: Ciphersuite> PublicCoordinator<C> {
+    pub fn new(threshold: u16, pubkey_package: frost::keys::PublicKeyPackage<C>) -> Self {
+        Self {
+            threshold,
+            pubkey_package,
+            endpoints: BTreeMap::new(),
+            commitment_pool: CommitmentPool::new(),
+            session_commitments: BTreeMap::new(),
+        }
+    }
+
+    /// Register the endpoint for one signer
+    ///
+    /// The signer must have a verifying share in the public key package, and each
+    /// signer can only be registered once.
+    pub fn add_signer(&mut self, transport: Box<dyn SignerTransport>) -> Result<(), ThresholdError> {
+        let index = transport.signer_index();
+        if !self.pubkey_package.verifying_shares().contains_key(&identifier(index)?) {
+            return Err(ThresholdError::UnknownSigner(index));
+        }
+        if self.endpoints.contains_key(&index) {
+            return Err(ThresholdError::InvalidParameters(format!("Signer {} is already registered", index)));
+        }
+
+        self.endpoints.insert(index, transport);
+        Ok(())
+    }
+
+    /// Indices of all registered signers
+    pub fn signer_indices(&self) -> Vec<u16> {
+        self.endpoints.keys().copied().collect()
+    }
+
+    /// Ask every registered signer to pregenerate `count` nonces and store the
+    /// commitments they publish
+    pub fn preprocess(&mut self, count: u16) -> Result<(), ThresholdError> {
+        for (&idx, transport) in self.endpoints.iter_mut() {
+            let serialized = match transport.send_preprocess_request(count)? {
+                SignerResponse::PreprocessedCommitments { signer_index, commitments } if signer_index == idx => commitments,
+                other => return Err(unexpected_response(idx, other)),
+            };
+            let commitments = serialized
+                .iter()
+                .map(|bytes| frost::round1::SigningCommitments::<C>::deserialize(bytes))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(codec("Invalid preprocessed commitments"))?;
+            self.commitment_pool.add(idx, commitments);
+        }
+        Ok(())
+    }
+
+    /// Number of preprocessed commitments left for `signer_index`
+    pub fn preprocessed_commitments(&self, signer_index: u16) -> usize {
+        self.commitment_pool.available(signer_index)
+    }
+
+    /// Perform complete threshold signing process with the given registered signers
+    ///
+    /// Uses one preprocessed commitment per signer when all of them have one left,
+    /// so only round 2 goes over the wire; otherwise runs both rounds.
+    pub fn perform_threshold_signing(
+        &mut self,
+        message: &[u8],
+        signer_indices: Vec<u16>,
+    ) -> Result<CombinedSignature, ThresholdError> {
+        self.sign(message, &signer_indices, |_| Ok(()))
+    }
+
+    /// Perform threshold signing under a rerandomized group key (see
+    /// `ThresholdCoordinator::perform_randomized_threshold_signing`)
+    ///
+    /// Preprocessed commitments are used just like in `perform_threshold_signing`.
+    pub fn perform_randomized_threshold_signing(
+        &mut self,
+        message: &[u8],
+        signer_indices: Vec<u16>,
+        randomizer: Option<Randomizer<C>>,
+    ) -> Result<CombinedSignature, ThresholdError>
+    where
+        C: RandomizedCiphersuite,
+    {
+        self.sign(message, &signer_indices, |session| randomize_session(session, randomizer))
+    }
+
+    /// Perform threshold signing for the child key at `path` (see
+    /// `ThresholdCoordinator::perform_threshold_signing_at`)
+    pub fn perform_threshold_signing_at(
+        &mut self,
+        path: &DerivationPath,
+        message: &[u8],
+        signer_indices: Vec<u16>,
+    ) -> Result<CombinedSignature, ThresholdError> {
+        let chain_code = group_chain_code(&self.pubkey_package)?;
+        self.sign(message, &signer_indices, |session| session.set_derivation(chain_code, path.clone()))
+    }
+
+    /// Fix the signing package with pooled or fresh commitments, let `prepare`
+    /// adjust the session, then run round 2
+    ///
+    /// If the attempt with pooled commitments fails, the signers are told to
+    /// discard the nonces behind them. Only a signer that can't be reached or no
+    /// longer holds its pooled nonces (e.g. after a restart) gets a retry with a
+    /// fresh round 1, after its remaining pooled commitments are dropped; any
+    /// other error, such as an invalid share, is returned as is.
+    fn sign(
+        &mut self,
+        message: &[u8],
+        signer_indices: &[u16],
+        prepare: impl Fn(&mut SigningSession<C>) -> Result<(), ThresholdError>,
+    ) -> Result<CombinedSignature, ThresholdError> {
+        self.check_signers(signer_indices)?;
+
+        let mut endpoints: Vec<&mut dyn SignerTransport> = self.endpoints
+            .iter_mut()
+            .filter(|(idx, _)| signer_indices.contains(idx))
+            .map(|(_, transport)| transport.as_mut() as &mut dyn SignerTransport)
+            .collect();
+
+        if let Some(commitments) = self.commitment_pool.take(signer_indices) {
+            let pooled = session_with_commitments(
+                self.threshold,
+                &self.pubkey_package,
+                message,
+                &endpoints,
+                commitments.clone(),
+            )
+            .and_then(|mut session| {
+                prepare(&mut session)?;
+                run_round2(&mut session, &mut endpoints)
+            });
+            let error = match pooled {
+                Ok(signature) => return Ok(signature),
+                Err(e) => e,
+            };
+            let stale_signer = match &error {
+                ThresholdError::Transport { signer_index, .. } | ThresholdError::UnknownNonces { signer_index } => {
+                    Some(*signer_index)
+                }
+                _ => None,
+            };
+            discard_pooled(&mut self.commitment_pool, &mut endpoints, &commitments, stale_signer);
+            if stale_signer.is_none() {
+                return Err(error);
+            }
+        }
+
+        let mut session = collect_commitments(self.threshold, &self.pubkey_package, message, &mut endpoints)?;
+        prepare(&mut session)?;
+        run_round2(&mut session, &mut endpoints)
+    }
+
+    /// Refuse unregistered or repeated signer indices
+    fn check_signers(&self, signer_indices: &[u16]) -> Result<(), ThresholdError> {
+        if let Some(unknown) = signer_indices.iter().find(|idx| !self.endpoints.contains_key(idx)) {
+            return Err(ThresholdError::UnknownSigner(*unknown));
+        }
+        check_distinct_signers(signer_indices)
+    }
+
+    /// Sign every message in `messages` with the given registered signers
+    ///
+    /// Signers generate separate nonces for each batch, outside their preprocessed
+    /// pool, and drop the unused ones when the batch ends. Returns one result per
+    /// message (see `ThresholdCoordinator::perform_batch_signing`).
+    pub fn perform_batch_signing<M: AsRef<[u8]>>(
+        &mut self,
+        messages: &[M],
+        signer_indices: Vec<u16>,
+    ) -> Result<Vec<Result<CombinedSignature, ThresholdError>>, ThresholdError> {
+        self.check_signers(&signer_indices)?;
+
+        let mut endpoints: Vec<&mut dyn SignerTransport> = self.endpoints
+            .iter_mut()
+            .filter(|(idx, _)| signer_indices.contains(idx))
+            .map(|(_, transport)| transport.as_mut() as &mut dyn SignerTransport)
+            .collect();
+
+        sign_batch_with_transports(self.threshold, &self.pubkey_package, messages, &mut endpoints)
+    }
+
+    /// Open a session for signing `message` with `signer_indices`, to be driven
+    /// by the caller as responses arrive (see `SigningSession`)
+    ///
+    /// If every signer has a preprocessed commitment left, the session starts
+    /// with them and its signing package is ready immediately. Hand the session
+    /// back to `release_session` once it is over or abandoned.
+    pub fn start_session(
+        &mut self,
+        message: &[u8],
+        signer_indices: &[u16],
+        timeout: Duration,
+    ) -> Result<SigningSession<C>, ThresholdError> {
+        self.check_signers(signer_indices)?;
+
+        let mut session = SigningSession::new(
+            self.threshold,
+            self.pubkey_package.clone(),
+            message,
+            signer_indices,
+            timeout,
+        )?;
+        if let Some(commitments) = self.commitment_pool.take(signer_indices) {
+            for (&idx, &commitment) in &commitments {
+                session.add_commitments(idx, commitment)?;
+            }
+            self.session_commitments.insert(session.id(), commitments);
+        }
+        Ok(session)
+    }
+
+    /// End a session opened by `start_session`
+    ///
+    /// Unless the session completed, the signers are told to discard the nonces
+    /// behind the preprocessed commitments it took, so a session that timed out,
+    /// failed or was given up on doesn't leave them held. Best effort, like the
+    /// discard after a failed `perform_threshold_signing`.
+    pub fn release_session(&mut self, session: SigningSession<C>) {
+        let Some(taken) = self.session_commitments.remove(&session.id()) else { return };
+        if matches!(session.state(), SessionState::Complete(_)) {
+            return;
+        }
+
+        let mut endpoints: Vec<&mut dyn SignerTransport> = self.endpoints
+            .values_mut()
+            .map(|transport| transport.as_mut() as &mut dyn SignerTransport)
+            .collect();
+        discard_pooled(&mut self.commitment_pool, &mut endpoints, &taken, None);
+    }
+
+    /// Perform threshold signing, excluding misbehaving signers and retrying
+    /// (see `ThresholdCoordinator::perform_robust_threshold_signing`)
+    pub fn perform_robust_threshold_signing(
+        &mut self,
+        message: &[u8],
+        signer_indices: Vec<u16>,
+    ) -> Result<(CombinedSignature, RobustSigningReport), ThresholdError> {
+        let threshold = self.threshold;
+        robust_signing(threshold, signer_indices, |candidates| {
+            self.perform_threshold_signing(message, candidates)
+        })
+    }
+
+    /// Combine serialized round 2 responses into the final signature
+    /// (see `ThresholdCoordinator::combine_signatures`)
+    pub fn combine_signatures(
+        &self,
+        signing_package: &[u8],
+        serialized_shares: Vec<Vec<u8>>,
+    ) -> Result<CombinedSignature, ThresholdError> {
+        combine_serialized_shares(&self.pubkey_package, None, signing_package, serialized_shares)
+    }
+}
+
+/// Release the nonces behind pooled commitments `taken` for a failed attempt
+///
+/// The remaining pooled commitments of `stale_signer` are dropped and released
+/// as well: its failure suggests they no longer match what it holds (e.g. it
+/// restarted). Best effort, since a signer that can't be reached can't be told.
+fn discard_pooled<C: Ciphersuite>(
+    pool: &mut CommitmentPool<C>,
+    endpoints: &mut [&mut dyn SignerTransport],
+    taken: &BTreeMap<u16, frost::round1::SigningCommitments<C>>,
+    stale_signer: Option<u16>,
+) {
+    for transport in endpoints.iter_mut() {
+        let idx = transport.signer_index();
+        let Some(commitment) = taken.get(&idx) else { continue };
+        let remaining = if stale_signer == Some(idx) { pool.clear_signer(idx) } else { Vec::new() };
+        let serialized: Vec<Vec<u8>> = std::iter::once(*commitment)
+            .chain(remaining)
+            .filter_map(|c| c.serialize().ok())
+            .collect();
+        transport.send_discard_request(&serialized).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::serialization::SignerMessage;
+    use crate::threshold::{ThresholdSigner, generate_frost_keys};
+    use crate::transport::InMemoryTransport;
+
+    fn coordinator_with_in_memory_signers(
+        max_signers: u16,
+        min_signers: u16,
+    ) -> PublicCoordinator<E> {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(max_signers, min_signers).unwrap();
+
+        let mut coordinator = PublicCoordinator::new(min_signers, pubkey_package);
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let signer = ThresholdSigner::new((i + 1) as u16, kp);
+            coordinator.add_signer(Box::new(InMemoryTransport::new(signer))).unwrap();
+        }
+        coordinator
+    }
+
+    #[test]
+    fn test_public_coordinator_signing() {
+        let mut coordinator = coordinator_with_in_memory_signers(5, 3);
+        assert_eq!(coordinator.signer_indices(), vec![1, 2, 3, 4, 5]);
+
+        let message = b"Hello, coordinator without secrets!";
+        let combined_sig = coordinator
+            .perform_threshold_signing(message, vec![2, 3, 5])
+            .unwrap();
+
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+
+        assert!(verifying_key.verify(message, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_public_coordinator_rejects_unknown_and_duplicate_signers() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let (other_key_packages, _) = generate_frost_keys::<E>(5, 2).unwrap();
+
+        let mut coordinator = PublicCoordinator::new(2, pubkey_package);
+        let signer = ThresholdSigner::new(1, key_packages[0].clone());
+        coordinator.add_signer(Box::new(InMemoryTransport::new(signer))).unwrap();
+
+        let duplicate = ThresholdSigner::new(1, key_packages[0].clone());
+        assert!(coordinator.add_signer(Box::new(InMemoryTransport::new(duplicate))).is_err());
+
+        // Index 5 has no verifying share in a 3-signer public key package
+        let outsider = ThresholdSigner::new(5, other_key_packages[4].clone());
+        assert_eq!(
+            coordinator.add_signer(Box::new(InMemoryTransport::new(outsider))),
+            Err(ThresholdError::UnknownSigner(5))
+        );
+
+        assert_eq!(
+            coordinator.perform_threshold_signing(b"message", vec![1, 2]).unwrap_err(),
+            ThresholdError::UnknownSigner(2)
+        );
+    }
+
+    #[test]
+    fn test_public_coordinator_signs_with_preprocessed_commitments() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let mut coordinator = PublicCoordinator::new(2, pubkey_package);
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let signer = ThresholdSigner::new((i + 1) as u16, kp);
+            coordinator.add_signer(Box::new(RoundCounter::new(signer))).unwrap();
+        }
+
+        coordinator.preprocess(2).unwrap();
+        assert_eq!(coordinator.preprocessed_commitments(1), 2);
+
+        // Two signatures use the preprocessed commitments: round 2 only
+        for message in [b"first".as_slice(), b"second".as_slice()] {
+            let combined_sig = coordinator.perform_threshold_signing(message, vec![1, 3]).unwrap();
+
+            use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+            let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+            let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+            assert!(verifying_key.verify(message, &signature).is_ok());
+        }
+        assert_eq!(coordinator.preprocessed_commitments(1), 0);
+        assert_eq!(coordinator.preprocessed_commitments(2), 2);
+        assert_eq!(ROUND1_REQUESTS.with(|c| c.get()), 0);
+
+        // Pool exhausted for signer 1: falls back to an interactive round 1
+        assert!(coordinator.perform_threshold_signing(b"third", vec![1, 2]).is_ok());
+        assert_eq!(ROUND1_REQUESTS.with(|c| c.get()), 2);
+        assert_eq!(coordinator.preprocessed_commitments(2), 2);
+    }
+
+    #[test]
+    fn test_public_coordinator_start_session_uses_preprocessed_commitments() {
+        let mut coordinator = coordinator_with_in_memory_signers(3, 2);
+        let timeout = Duration::from_secs(60);
+
+        assert_eq!(
+            coordinator.start_session(b"message", &[1, 4], timeout).err(),
+            Some(ThresholdError::UnknownSigner(4))
+        );
+
+        let session = coordinator.start_session(b"message", &[1, 2], timeout).unwrap();
+        assert!(session.signing_package().is_none());
+        assert_eq!(session.pending(), vec![1, 2]);
+
+        coordinator.preprocess(1).unwrap();
+        let session = coordinator.start_session(b"message", &[1, 2], timeout).unwrap();
+        assert!(session.signing_package().is_some());
+        assert_eq!(coordinator.preprocessed_commitments(1), 0);
+    }
+
+    #[test]
+    fn test_public_coordinator_release_session_discards_pooled_nonces() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let mut coordinator = PublicCoordinator::new(2, pubkey_package);
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let signer = ThresholdSigner::new((i + 1) as u16, kp);
+            coordinator.add_signer(Box::new(FlakyRound2::new(signer, false))).unwrap();
+        }
+        coordinator.preprocess(2).unwrap();
+        let timeout = Duration::from_secs(60);
+
+        // Abandoned before round 2: both signers drop the nonce it took
+        let abandoned = coordinator.start_session(b"abandoned", &[1, 2], timeout).unwrap();
+        coordinator.release_session(abandoned);
+        assert_eq!(DISCARDED_NONCES.with(|c| c.get()), 2);
+
+        // Timed out: the same
+        let mut timed_out = coordinator.start_session(b"timed out", &[1, 3], timeout).unwrap();
+        assert!(timed_out.check_deadline(timed_out.deadline() + timeout).is_err());
+        coordinator.release_session(timed_out);
+        assert_eq!(DISCARDED_NONCES.with(|c| c.get()), 4);
+
+        // Nothing pooled was taken, so nothing to discard
+        let interactive = coordinator.start_session(b"interactive", &[1, 2], timeout).unwrap();
+        coordinator.release_session(interactive);
+        assert_eq!(DISCARDED_NONCES.with(|c| c.get()), 4);
+        assert_eq!(coordinator.preprocessed_commitments(3), 1);
+    }
+
+    thread_local! {
+        static ROUND1_REQUESTS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    /// In-memory transport that counts interactive round 1 requests
+    struct RoundCounter(InMemoryTransport<E>);
+
+    impl RoundCounter {
+        fn new(signer: ThresholdSigner<E>) -> Self {
+            Self(InMemoryTransport::new(signer))
+        }
+    }
+
+    impl SignerTransport for RoundCounter {
+        fn signer_index(&self) -> u16 {
+            self.0.signer_index()
+        }
+
+        fn exchange(&mut self, request: &SignerMessage) -> Result<SignerResponse, ThresholdError> {
+            self.0.exchange(request)
+        }
+
+        fn send_round1_request(&mut self) -> Result<SignerResponse, ThresholdError> {
+            ROUND1_REQUESTS.with(|c| c.set(c.get() + 1));
+            self.0.send_round1_request()
+        }
+    }
+
+    #[test]
+    fn test_public_coordinator_rejects_duplicate_signers() {
+        let mut coordinator = coordinator_with_in_memory_signers(3, 2);
+        coordinator.preprocess(1).unwrap();
+
+        assert!(matches!(
+            coordinator.perform_threshold_signing(b"message", vec![1, 1, 2]),
+            Err(ThresholdError::InvalidParameters(_))
+        ));
+        assert_eq!(coordinator.preprocessed_commitments(1), 1);
+        assert_eq!(coordinator.preprocessed_commitments(2), 1);
+    }
+
+    #[test]
+    fn test_public_coordinator_discards_pooled_nonces_after_failure() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let mut coordinator = PublicCoordinator::new(2, pubkey_package);
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let index = (i + 1) as u16;
+            let signer = ThresholdSigner::new(index, kp);
+            coordinator.add_signer(Box::new(FlakyRound2::new(signer, index == 2))).unwrap();
+        }
+        coordinator.preprocess(2).unwrap();
+
+        // Signer 2's first round 2 request fails after signer 1 has used its pooled
+        // nonce; the retry runs a fresh round 1
+        let message = b"message";
+        let combined_sig = coordinator.perform_threshold_signing(message, vec![1, 2]).unwrap();
+
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+        assert!(verifying_key.verify(message, &signature).is_ok());
+
+        // Both of signer 2's pooled nonces were released; signer 1 used the one it
+        // was given and keeps the rest of its pool
+        assert_eq!(DISCARDED_NONCES.with(|c| c.get()), 2);
+        assert_eq!(coordinator.preprocessed_commitments(1), 1);
+        assert_eq!(coordinator.preprocessed_commitments(2), 0);
+        assert_eq!(coordinator.preprocessed_commitments(3), 2);
+    }
+
+    #[test]
+    fn test_public_coordinator_does_not_retry_invalid_shares() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let (other_key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let mut coordinator = PublicCoordinator::new(2, pubkey_package);
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let mut signer = ThresholdSigner::new((i + 1) as u16, kp);
+            if signer.index == 2 {
+                // Signs with a share from another group, so its shares fail verification
+                let kp = &signer.key_package;
+                signer.key_package = frost::keys::KeyPackage::new(
+                    *kp.identifier(),
+                    *other_key_packages[1].signing_share(),
+                    *kp.verifying_share(),
+                    *kp.verifying_key(),
+                    *kp.min_signers(),
+                );
+            }
+            coordinator.add_signer(Box::new(RoundCounter::new(signer))).unwrap();
+        }
+        coordinator.preprocess(2).unwrap();
+
+        assert_eq!(
+            coordinator.perform_threshold_signing(b"message", vec![1, 2]),
+            Err(ThresholdError::ShareVerification { culprits: vec![2] })
+        );
+        // No second round with the same set, and no pool was cleared
+        assert_eq!(ROUND1_REQUESTS.with(|c| c.get()), 0);
+        assert_eq!(coordinator.preprocessed_commitments(1), 1);
+        assert_eq!(coordinator.preprocessed_commitments(2), 1);
+    }
+
+    thread_local! {
+        static DISCARDED_NONCES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    /// In-memory transport that can fail its first round 2 request, and counts
+    /// the nonces its signer discards
+    struct FlakyRound2 {
+        inner: InMemoryTransport<E>,
+        fail_next_round2: bool,
+    }
+
+    impl FlakyRound2 {
+        fn new(signer: ThresholdSigner<E>, fail_first_round2: bool) -> Self {
+            Self {
+                inner: InMemoryTransport::new(signer),
+                fail_next_round2: fail_first_round2,
+            }
+        }
+    }
+
+    impl SignerTransport for FlakyRound2 {
+        fn signer_index(&self) -> u16 {
+            self.inner.signer_index()
+        }
+
+        fn exchange(&mut self, request: &SignerMessage) -> Result<SignerResponse, ThresholdError> {
+            self.inner.exchange(request)
+        }
+
+        fn send_signing_package(&mut self, signing_package: &[u8]) -> Result<SignerResponse, ThresholdError> {
+            if std::mem::take(&mut self.fail_next_round2) {
+                return Err(ThresholdError::Transport {
+                    signer_index: self.signer_index(),
+                    reason: "connection reset".to_string(),
+                });
+            }
+            self.inner.send_signing_package(signing_package)
+        }
+
+        fn send_discard_request(&mut self, commitments: &[Vec<u8>]) -> Result<SignerResponse, ThresholdError> {
+            let response = self.inner.send_discard_request(commitments)?;
+            if let SignerResponse::NoncesDiscarded { discarded, .. } = response {
+                DISCARDED_NONCES.with(|c| c.set(c.get() + discarded as usize));
+            }
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn test_public_coordinator_robust_signing() {
+        let mut coordinator = coordinator_with_in_memory_signers(5, 3);
+
+        let (combined_sig, report) = coordinator
+            .perform_robust_threshold_signing(b"message", vec![1, 2, 3, 4, 5])
+            .unwrap();
+
+        assert!(report.excluded_signers.is_empty());
+        assert_eq!(combined_sig.public_key.len(), 32);
+    }
+}
@@ -0,0 +1,295 @@
+//! Non-hardened additive key derivation from the group key
+//!
+//! One committee controls a whole tree of child keys without running a new DKG
+//! for each. Each step from a parent key `K` with chain code `c` to child `i`
+//! computes a public tweak `t` from `(c, K, i)` and the child key `K + t·G`.
+//! The tweaks along a path add up, so every signer shifts its share by the same
+//! sum and the signature verifies under the child key.
+//!
+//! Derivation uses only public data: anyone with the group key and chain code
+//! can compute every child key, e.g. to generate addresses. Knowing a child
+//! signing key reveals the group secret, which is why there are no hardened
+//! indices here.
+
+use frost_core::keys::{KeyPackage, PublicKeyPackage};
+use frost_core::{Ciphersuite, Field, Scalar, VerifyingKey};
+use frost_rerandomized::RandomizedParams;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{ThresholdError, codec};
+use crate::rerandomized::{Randomizer, randomize_key_package, randomize_public_key_package};
+use crate::reshare::ScalarField;
+
+/// 32 bytes of public entropy mixed into every derivation step
+pub type ChainCode = [u8; 32];
+
+/// Deepest derivation path accepted
+pub const MAX_DERIVATION_DEPTH: usize = 255;
+
+/// Indices from here on are hardened in BIP-32 and cannot be derived additively
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// Sequence of non-hardened child indices, written `m/0/7`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Path through `indices`; each must be below `HARDENED_OFFSET`
+    pub fn new(indices: Vec<u32>) -> Result<Self, ThresholdError> {
+        if indices.len() > MAX_DERIVATION_DEPTH {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "Derivation path of depth {} exceeds the limit of {}",
+                indices.len(),
+                MAX_DERIVATION_DEPTH
+            )));
+        }
+        if let Some(index) = indices.iter().find(|&&i| i >= HARDENED_OFFSET) {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "Hardened index {} cannot be derived from the group key",
+                index
+            )));
+        }
+        Ok(Self(indices))
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// This path extended by one more `index`
+    pub fn child(&self, index: u32) -> Result<Self, ThresholdError> {
+        Self::new([self.0.as_slice(), &[index]].concat())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = ThresholdError;
+
+    /// Parse `m`, `m/0` or `m/0/7`; hardened components (`0'`, `0h`) are refused
+    fn from_str(s: &str) -> Result<Self, ThresholdError> {
+        let invalid = || ThresholdError::InvalidParameters(format!("Invalid derivation path {:?}", s));
+        let mut components = s.split('/');
+        if components.next() != Some("m") {
+            return Err(invalid());
+        }
+        let indices = components
+            .map(|c| {
+                if c.ends_with(['\'', 'h', 'H']) {
+                    return Err(ThresholdError::InvalidParameters(format!(
+                        "Hardened index {} cannot be derived from the group key",
+                        c
+                    )));
+                }
+                c.parse::<u32>().map_err(|_| invalid())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(indices)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            write!(f, "/{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// A verifying key together with the chain code its children are derived with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedVerifyingKey<C: Ciphersuite> {
+    verifying_key: VerifyingKey<C>,
+    chain_code: ChainCode,
+}
+
+impl<C: Ciphersuite> ExtendedVerifyingKey<C> {
+    pub fn new(verifying_key: VerifyingKey<C>, chain_code: ChainCode) -> Self {
+        Self {
+            verifying_key,
+            chain_code,
+        }
+    }
+
+    /// Root of the group's key tree, with the chain code from `group_chain_code`
+    pub fn from_group(pubkey_package: &PublicKeyPackage<C>) -> Result<Self, ThresholdError> {
+        Ok(Self::new(*pubkey_package.verifying_key(), group_chain_code(pubkey_package)?))
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey<C> {
+        &self.verifying_key
+    }
+
+    pub fn chain_code(&self) -> &ChainCode {
+        &self.chain_code
+    }
+
+    /// The extended key at `path` below this one
+    pub fn derive(&self, path: &DerivationPath) -> Result<Self, ThresholdError> {
+        Ok(self.derive_with_tweak(path)?.0)
+    }
+
+    /// The extended key at `path` and the sum of the tweaks leading there
+    pub(crate) fn derive_with_tweak(&self, path: &DerivationPath) -> Result<(Self, Scalar<C>), ThresholdError> {
+        let mut key = *self;
+        let mut tweak = <ScalarField<C>>::zero();
+        for &index in path.indices() {
+            let parent = key
+                .verifying_key
+                .serialize()
+                .map_err(codec("Failed to serialize verifying key"))?;
+            let input = [key.chain_code.as_slice(), &parent, &index.to_be_bytes()].concat();
+
+            let step = C::H3(&[b"FROST-HD tweak".as_slice(), &input].concat());
+            let chain_code = Sha256::digest([b"FROST-HD chain code".as_slice(), &input].concat()).into();
+            let verifying_key = *RandomizedParams::from_randomizer(&key.verifying_key, Randomizer::from_scalar(step))
+                .randomized_verifying_key();
+
+            key = Self::new(verifying_key, chain_code);
+            tweak = tweak + step;
+        }
+        Ok((key, tweak))
+    }
+}
+
+/// Default chain code of a group, computed from its verifying key
+///
+/// Any 32 public bytes work as a chain code; this one needs no extra setup, and
+/// every signer can recompute it from its own key package.
+pub fn group_chain_code<C: Ciphersuite>(pubkey_package: &PublicKeyPackage<C>) -> Result<ChainCode, ThresholdError> {
+    let group_key = pubkey_package
+        .verifying_key()
+        .serialize()
+        .map_err(codec("Failed to serialize verifying key"))?;
+    Ok(Sha256::digest([b"FROST-HD root chain code".as_slice(), C::ID.as_bytes(), &group_key].concat()).into())
+}
+
+/// Public key package of the child key at `path`, for aggregating its signatures
+pub fn derive_public_key_package<C: Ciphersuite>(
+    pubkey_package: &PublicKeyPackage<C>,
+    chain_code: &ChainCode,
+    path: &DerivationPath,
+) -> Result<PublicKeyPackage<C>, ThresholdError> {
+    let root = ExtendedVerifyingKey::new(*pubkey_package.verifying_key(), *chain_code);
+    let (_, tweak) = root.derive_with_tweak(path)?;
+    Ok(randomize_public_key_package(pubkey_package, &Randomizer::from_scalar(tweak)))
+}
+
+/// A signer's key package for the child key at `path`
+pub fn derive_key_package<C: Ciphersuite>(
+    key_package: &KeyPackage<C>,
+    chain_code: &ChainCode,
+    path: &DerivationPath,
+) -> Result<KeyPackage<C>, ThresholdError> {
+    let root = ExtendedVerifyingKey::new(*key_package.verifying_key(), *chain_code);
+    let (_, tweak) = root.derive_with_tweak(path)?;
+    randomize_key_package(key_package, &Randomizer::from_scalar(tweak))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::coordinator::PublicCoordinator;
+    use crate::threshold::{ThresholdCoordinator, ThresholdSigner, generate_frost_keys};
+    use crate::transport::InMemoryTransport;
+
+    #[test]
+    fn test_derivation_path_parsing() {
+        let path: DerivationPath = "m/0/7/2147483647".parse().unwrap();
+        assert_eq!(path.indices(), &[0, 7, HARDENED_OFFSET - 1]);
+        assert_eq!(path.to_string(), "m/0/7/2147483647");
+        assert_eq!("m".parse::<DerivationPath>().unwrap(), DerivationPath::default());
+
+        for invalid in ["", "0/1", "m/", "m/x", "m//1", "m/1/", "m/-1", "m/0'", "m/0h", "m/2147483648"] {
+            assert!(
+                matches!(invalid.parse::<DerivationPath>(), Err(ThresholdError::InvalidParameters(_))),
+                "{:?} parsed",
+                invalid
+            );
+        }
+        assert!(DerivationPath::new(vec![0; MAX_DERIVATION_DEPTH + 1]).is_err());
+    }
+
+    #[test]
+    fn test_child_keys_compose_and_differ() {
+        let (_, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let root = ExtendedVerifyingKey::from_group(&pubkey_package).unwrap();
+        let path: DerivationPath = "m/1/2".parse().unwrap();
+
+        let child = root.derive(&path).unwrap();
+        assert_eq!(root.derive(&DerivationPath::default()).unwrap(), root);
+        assert_eq!(child, root.derive(&"m/1".parse().unwrap()).unwrap().derive(&"m/2".parse().unwrap()).unwrap());
+        assert_ne!(child.verifying_key(), root.derive(&"m/2/1".parse().unwrap()).unwrap().verifying_key());
+
+        // Another chain code gives unrelated children of the same group key
+        let other = ExtendedVerifyingKey::new(*pubkey_package.verifying_key(), [1u8; 32]);
+        assert_ne!(other.derive(&path).unwrap().verifying_key(), child.verifying_key());
+
+        let child_package = derive_public_key_package(&pubkey_package, root.chain_code(), &path).unwrap();
+        assert_eq!(child_package.verifying_key(), child.verifying_key());
+    }
+
+    #[test]
+    fn test_signing_at_path_verifies_under_child_key() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+        let signers = key_packages
+            .iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp.clone()))
+            .collect();
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package.clone());
+        let root = ExtendedVerifyingKey::from_group(&pubkey_package).unwrap();
+        let message = b"pay from address 4";
+
+        for path in ["m/4", "m/0/1/2", "m"] {
+            let path: DerivationPath = path.parse().unwrap();
+            let child_key = root.derive(&path).unwrap().verifying_key().serialize().unwrap();
+
+            let signature = coordinator.perform_threshold_signing_at(&path, message, vec![1, 3, 5]).unwrap();
+            assert_eq!(signature.public_key, child_key);
+            assert!(signature.verify::<E>(message));
+        }
+
+        // A child key package signs on its own, e.g. after being exported to another coordinator
+        let path: DerivationPath = "m/4".parse().unwrap();
+        let child_signers = key_packages
+            .iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, derive_key_package(kp, root.chain_code(), &path).unwrap()))
+            .collect();
+        let child_pubkey_package = derive_public_key_package(&pubkey_package, root.chain_code(), &path).unwrap();
+        let mut child_coordinator = ThresholdCoordinator::new(3, child_signers, child_pubkey_package);
+        let signature = child_coordinator.perform_threshold_signing(message, vec![2, 3, 4]).unwrap();
+        assert_eq!(signature.public_key, root.derive(&path).unwrap().verifying_key().serialize().unwrap());
+        assert!(signature.verify::<E>(message));
+    }
+
+    #[test]
+    fn test_public_coordinator_signs_at_path() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let child_key = ExtendedVerifyingKey::from_group(&pubkey_package)
+            .unwrap()
+            .derive(&"m/9".parse().unwrap())
+            .unwrap()
+            .verifying_key()
+            .serialize()
+            .unwrap();
+        let mut coordinator = PublicCoordinator::new(2, pubkey_package);
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let signer = ThresholdSigner::new((i + 1) as u16, kp);
+            coordinator.add_signer(Box::new(InMemoryTransport::new(signer))).unwrap();
+        }
+        coordinator.preprocess(1).unwrap();
+
+        let signature = coordinator
+            .perform_threshold_signing_at(&"m/9".parse().unwrap(), b"message", vec![2, 3])
+            .unwrap();
+        assert_eq!(signature.public_key, child_key);
+        assert!(signature.verify::<E>(b"message"));
+    }
+}
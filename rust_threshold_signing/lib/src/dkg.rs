@@ -0,0 +1,433 @@
+use frost_core::{self as frost, Ciphersuite};
+use frost::keys::dkg::{part1, part2, part3, round1, round2};
+use frost::keys::refresh::{refresh_dkg_part_1, refresh_dkg_part2, refresh_dkg_shares};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::{ThresholdError, identifier};
+use crate::serialization::{decode, encode, serialize, try_deserialize};
+
+/// Round 1 DKG message, broadcast by `sender` to every other participant
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(bound = "C: Ciphersuite")]
+pub struct DkgRound1Message<C: Ciphersuite> {
+    pub sender: u16,
+    pub package: round1::Package<C>,
+}
+
+/// Round 2 DKG message, sent privately from `sender` to `recipient`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(bound = "C: Ciphersuite")]
+pub struct DkgRound2Message<C: Ciphersuite> {
+    pub sender: u16,
+    pub recipient: u16,
+    pub package: round2::Package<C>,
+}
+
+/// Where a participant currently is in the key ceremony
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "C: Ciphersuite")]
+enum DkgState<C: Ciphersuite> {
+    /// `start()` has not been called yet
+    Initialized,
+    /// Round 1 package broadcast, waiting for everyone else's
+    Round1 {
+        secret_package: round1::SecretPackage<C>,
+    },
+    /// Round 2 packages sent, collecting the ones addressed to us
+    Round2 {
+        secret_package: round2::SecretPackage<C>,
+        round1_packages: BTreeMap<frost::Identifier<C>, round1::Package<C>>,
+        round2_packages: BTreeMap<frost::Identifier<C>, round2::Package<C>>,
+    },
+    /// Key package derived, no secret DKG state left
+    Finished,
+}
+
+/// Existing key material of a participant refreshing its share
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "C: Ciphersuite")]
+struct RefreshInputs<C: Ciphersuite> {
+    key_package: frost::keys::KeyPackage<C>,
+    pubkey_package: frost::keys::PublicKeyPackage<C>,
+}
+
+/// One party of a distributed key generation ceremony
+///
+/// Holds only this party's secret packages, so each participant can run in a
+/// separate process and exchange the serialized messages over any channel.
+/// Round 1 messages must be broadcast to everyone; round 2 messages must be
+/// delivered privately to their `recipient`.
+///
+/// The same state machine runs a proactive share refresh (see `for_refresh`):
+/// every party then deals a sharing of zero instead of a fresh secret.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "C: Ciphersuite")]
+pub struct DkgParticipant<C: Ciphersuite> {
+    pub index: u16,
+    pub max_signers: u16,
+    pub min_signers: u16,
+    identifier: frost::Identifier<C>,
+    refresh: Option<RefreshInputs<C>>,
+    state: DkgState<C>,
+}
+
+impl<C: Ciphersuite> DkgParticipant<C> {
+    pub fn new(index: u16, max_signers: u16, min_signers: u16) -> Result<Self, ThresholdError> {
+        if index == 0 || index > max_signers {
+            return Err(ThresholdError::InvalidIdentifier(index));
+        }
+        let identifier = identifier(index)?;
+
+        Ok(Self {
+            index,
+            max_signers,
+            min_signers,
+            identifier,
+            refresh: None,
+            state: DkgState::Initialized,
+        })
+    }
+
+    /// Participant in a proactive refresh of an existing key
+    ///
+    /// Each party deals a random sharing of zero and adds the shares it receives
+    /// to its current signing share. The group verifying key is unchanged, but
+    /// every signing share (and verifying share) is rerandomized, so shares from
+    /// before the refresh are useless alongside shares from after it. Every
+    /// holder listed in `pubkey_package` must take part.
+    pub fn for_refresh(
+        index: u16,
+        key_package: frost::keys::KeyPackage<C>,
+        pubkey_package: frost::keys::PublicKeyPackage<C>,
+    ) -> Result<Self, ThresholdError> {
+        let max_signers = u16::try_from(pubkey_package.verifying_shares().len()).map_err(|_| {
+            ThresholdError::InvalidParameters("Too many participants in public key package".to_string())
+        })?;
+        let mut participant = Self::new(index, max_signers, *key_package.min_signers())?;
+
+        if *key_package.identifier() != participant.identifier {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "Key package does not belong to participant {}",
+                index
+            )));
+        }
+        if !pubkey_package.verifying_shares().contains_key(&participant.identifier) {
+            return Err(ThresholdError::UnknownSigner(index));
+        }
+
+        participant.refresh = Some(RefreshInputs {
+            key_package,
+            pubkey_package,
+        });
+        Ok(participant)
+    }
+
+    /// Part 1: Generate our secret polynomial and return the serialized
+    /// `DkgRound1Message` to broadcast to every other participant
+    pub fn start(&mut self) -> Result<Vec<u8>, ThresholdError> {
+        if !matches!(self.state, DkgState::Initialized) {
+            return Err(ThresholdError::Protocol(format!("DKG participant {} already started", self.index)));
+        }
+
+        let mut rng = thread_rng();
+        let (secret_package, package) = if self.refresh.is_some() {
+            refresh_dkg_part_1(self.identifier, self.max_signers, self.min_signers, &mut rng)
+        } else {
+            part1(self.identifier, self.max_signers, self.min_signers, &mut rng)
+        }.map_err(|e| ThresholdError::KeyGeneration(format!("DKG part 1 failed: {:?}", e)))?;
+
+        self.state = DkgState::Round1 { secret_package };
+
+        Ok(encode::<C, _>(&DkgRound1Message {
+            sender: self.index,
+            package,
+        }))
+    }
+
+    /// Part 2: Process the round 1 messages of all other participants and return
+    /// the serialized `DkgRound2Message`s to send, keyed by recipient index
+    pub fn receive_round1(&mut self, messages: &[Vec<u8>]) -> Result<BTreeMap<u16, Vec<u8>>, ThresholdError> {
+        let secret_package = match std::mem::replace(&mut self.state, DkgState::Finished) {
+            DkgState::Round1 { secret_package } => secret_package,
+            state => {
+                self.state = state;
+                return Err(ThresholdError::Protocol(format!(
+                    "DKG participant {} is not expecting round 1 messages",
+                    self.index
+                )));
+            }
+        };
+
+        let result = self.collect_round1(messages).and_then(|round1_packages| {
+            if self.refresh.is_some() {
+                refresh_dkg_part2(secret_package.clone(), &round1_packages)
+            } else {
+                part2(secret_package.clone(), &round1_packages)
+            }
+                .map(|(secret, outgoing)| (secret, outgoing, round1_packages))
+                .map_err(|e| ThresholdError::KeyGeneration(format!("DKG part 2 failed: {:?}", e)))
+        });
+        let (secret, outgoing, round1_packages) = match result {
+            Ok(output) => output,
+            Err(e) => {
+                // Leave the participant in round 1 so the caller can retry with the right set
+                self.state = DkgState::Round1 { secret_package };
+                return Err(e);
+            }
+        };
+
+        let mut messages = BTreeMap::new();
+        for (recipient, package) in outgoing {
+            let recipient_index = self.index_of(recipient)?;
+            let message = DkgRound2Message {
+                sender: self.index,
+                recipient: recipient_index,
+                package,
+            };
+            messages.insert(recipient_index, encode::<C, _>(&message));
+        }
+
+        self.state = DkgState::Round2 {
+            secret_package: secret,
+            round1_packages,
+            round2_packages: BTreeMap::new(),
+        };
+
+        Ok(messages)
+    }
+
+    /// Store round 2 messages addressed to this participant
+    ///
+    /// May be called several times as messages arrive; `finish()` checks that
+    /// one message from every other participant has been received.
+    pub fn receive_round2(&mut self, messages: &[Vec<u8>]) -> Result<(), ThresholdError> {
+        let index = self.index;
+        let max_signers = self.max_signers;
+        let DkgState::Round2 { round2_packages, .. } = &mut self.state else {
+            return Err(ThresholdError::Protocol(format!(
+                "DKG participant {} is not expecting round 2 messages",
+                index
+            )));
+        };
+
+        for bytes in messages {
+            let message: DkgRound2Message<C> = decode::<C, _>(bytes)?;
+            if message.recipient != index {
+                return Err(ThresholdError::Protocol(format!(
+                    "Round 2 message for participant {} delivered to participant {}",
+                    message.recipient, index
+                )));
+            }
+            if message.sender == index || message.sender == 0 || message.sender > max_signers {
+                return Err(ThresholdError::InvalidIdentifier(message.sender));
+            }
+            let sender = identifier(message.sender)?;
+            if round2_packages.insert(sender, message.package).is_some() {
+                return Err(ThresholdError::Protocol(format!(
+                    "Duplicate round 2 message from participant {}",
+                    message.sender
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Part 3: Derive this participant's key package and the group's public key package
+    pub fn finish(&mut self) -> Result<(frost::keys::KeyPackage<C>, frost::keys::PublicKeyPackage<C>), ThresholdError> {
+        let DkgState::Round2 { secret_package, round1_packages, round2_packages } = &self.state else {
+            return Err(ThresholdError::Protocol(format!("DKG participant {} is not ready to finish", self.index)));
+        };
+
+        let output = match &self.refresh {
+            Some(old) => refresh_dkg_shares(
+                secret_package,
+                round1_packages,
+                round2_packages,
+                old.pubkey_package.clone(),
+                old.key_package.clone(),
+            ),
+            None => part3(secret_package, round1_packages, round2_packages),
+        }.map_err(|e| ThresholdError::KeyGeneration(format!("DKG part 3 failed for participant {}: {:?}", self.index, e)))?;
+
+        self.state = DkgState::Finished;
+
+        Ok(output)
+    }
+
+    /// Serialize the participant, secret packages included, so the ceremony can
+    /// continue in a later process
+    ///
+    /// The bytes hold this party's secret polynomial and the shares it received:
+    /// keep them encrypted (see `keystore::save_dkg_state`) and delete them once
+    /// `finish()` has returned.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serialize(self)
+    }
+
+    /// Restore a participant saved with `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ThresholdError> {
+        let participant: Self = try_deserialize(bytes)?;
+        if identifier(participant.index)? != participant.identifier {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "DKG state claims participant {} but holds a different identifier",
+                participant.index
+            )));
+        }
+        Ok(participant)
+    }
+
+    fn collect_round1(&self, messages: &[Vec<u8>]) -> Result<BTreeMap<frost::Identifier<C>, round1::Package<C>>, ThresholdError> {
+        let mut packages = BTreeMap::new();
+        for bytes in messages {
+            let message: DkgRound1Message<C> = decode::<C, _>(bytes)?;
+            if message.sender == self.index {
+                continue;
+            }
+            if message.sender == 0 || message.sender > self.max_signers {
+                return Err(ThresholdError::InvalidIdentifier(message.sender));
+            }
+            let sender = identifier(message.sender)?;
+            if packages.insert(sender, message.package).is_some() {
+                return Err(ThresholdError::Protocol(format!(
+                    "Duplicate round 1 message from participant {}",
+                    message.sender
+                )));
+            }
+        }
+        Ok(packages)
+    }
+
+    fn index_of(&self, id: frost::Identifier<C>) -> Result<u16, ThresholdError> {
+        (1..=self.max_signers)
+            .find(|&i| identifier(i).ok() == Some(id))
+            .ok_or_else(|| ThresholdError::Protocol(format!("Unknown DKG participant {:?}", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+
+    fn run_round1(participants: &mut [DkgParticipant<E>]) -> Vec<Vec<u8>> {
+        participants
+            .iter_mut()
+            .map(|p| p.start().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_dkg_participants_reach_same_group_key() {
+        let mut participants: Vec<DkgParticipant<E>> = (1..=4)
+            .map(|i| DkgParticipant::<E>::new(i, 4, 3).unwrap())
+            .collect();
+
+        let round1_messages = run_round1(&mut participants);
+
+        let mut inboxes: BTreeMap<u16, Vec<Vec<u8>>> = BTreeMap::new();
+        for participant in participants.iter_mut() {
+            let outgoing = participant.receive_round1(&round1_messages).unwrap();
+            assert_eq!(outgoing.len(), 3);
+            for (recipient, bytes) in outgoing {
+                inboxes.entry(recipient).or_default().push(bytes);
+            }
+        }
+
+        let mut pubkey_packages = Vec::new();
+        for participant in participants.iter_mut() {
+            participant.receive_round2(&inboxes[&participant.index]).unwrap();
+            let (key_package, pubkey_package) = participant.finish().unwrap();
+            assert_eq!(key_package.verifying_key(), pubkey_package.verifying_key());
+            pubkey_packages.push(pubkey_package);
+        }
+
+        assert!(pubkey_packages.windows(2).all(|w| w[0] == w[1]));
+    }
+
+    #[test]
+    fn test_dkg_participant_resumes_from_saved_state() {
+        let mut participants: Vec<DkgParticipant<E>> = (1..=3)
+            .map(|i| DkgParticipant::<E>::new(i, 3, 2).unwrap())
+            .collect();
+        let round1_messages = run_round1(&mut participants);
+
+        // Participant 1 stops after every round and picks up from its saved bytes
+        let mut restored = DkgParticipant::<E>::from_bytes(&participants[0].to_bytes()).unwrap();
+        let mut inboxes: BTreeMap<u16, Vec<Vec<u8>>> = BTreeMap::new();
+        for outgoing in std::iter::once(restored.receive_round1(&round1_messages).unwrap())
+            .chain(participants[1..].iter_mut().map(|p| p.receive_round1(&round1_messages).unwrap()))
+        {
+            for (recipient, bytes) in outgoing {
+                inboxes.entry(recipient).or_default().push(bytes);
+            }
+        }
+        let mut restored = DkgParticipant::<E>::from_bytes(&restored.to_bytes()).unwrap();
+        restored.receive_round2(&inboxes[&1]).unwrap();
+        let (_, restored_pubkey_package) = restored.finish().unwrap();
+
+        participants[1].receive_round2(&inboxes[&2]).unwrap();
+        let (_, pubkey_package) = participants[1].finish().unwrap();
+        assert_eq!(restored_pubkey_package, pubkey_package);
+        assert!(DkgParticipant::<E>::from_bytes(b"not a participant").is_err());
+    }
+
+    #[test]
+    fn test_dkg_participant_rejects_out_of_order_transitions() {
+        let mut participant = DkgParticipant::<E>::new(1, 3, 2).unwrap();
+
+        assert!(participant.receive_round1(&[]).is_err());
+        assert!(participant.receive_round2(&[]).is_err());
+        assert!(participant.finish().is_err());
+
+        participant.start().unwrap();
+        assert!(participant.start().is_err());
+        assert!(participant.finish().is_err());
+    }
+
+    #[test]
+    fn test_dkg_participant_rejects_misrouted_round2_message() {
+        let mut participants: Vec<DkgParticipant<E>> = (1..=3)
+            .map(|i| DkgParticipant::<E>::new(i, 3, 2).unwrap())
+            .collect();
+
+        let round1_messages = run_round1(&mut participants);
+        let outgoing_from_1 = participants[0].receive_round1(&round1_messages).unwrap();
+        participants[1].receive_round1(&round1_messages).unwrap();
+
+        // Participant 2 must not accept the message participant 1 addressed to participant 3
+        assert!(participants[1].receive_round2(&[outgoing_from_1[&3].clone()]).is_err());
+    }
+
+    #[test]
+    fn test_dkg_participant_retries_round1_with_missing_messages() {
+        let mut participants: Vec<DkgParticipant<E>> = (1..=3)
+            .map(|i| DkgParticipant::<E>::new(i, 3, 2).unwrap())
+            .collect();
+
+        let round1_messages = run_round1(&mut participants);
+
+        // Missing participant 3's package: part 2 fails but the participant can retry
+        assert!(participants[0].receive_round1(&round1_messages[..2]).is_err());
+        assert!(participants[0].receive_round1(&round1_messages).is_ok());
+    }
+
+    #[test]
+    fn test_refresh_participant_rejects_foreign_key_package() {
+        let (key_packages, pubkey_package) = crate::threshold::generate_frost_keys::<E>(3, 2).unwrap();
+        let (_, other_pubkey_package) = crate::threshold::generate_frost_keys::<E>(2, 2).unwrap();
+
+        assert!(DkgParticipant::for_refresh(1, key_packages[0].clone(), pubkey_package.clone()).is_ok());
+        assert!(DkgParticipant::for_refresh(2, key_packages[0].clone(), pubkey_package).is_err());
+        // Participant 3 has no verifying share in a 2-signer public key package
+        assert!(DkgParticipant::for_refresh(3, key_packages[2].clone(), other_pubkey_package).is_err());
+    }
+
+    #[test]
+    fn test_dkg_participant_rejects_invalid_index() {
+        assert!(DkgParticipant::<E>::new(0, 3, 2).is_err());
+        assert!(DkgParticipant::<E>::new(4, 3, 2).is_err());
+    }
+}
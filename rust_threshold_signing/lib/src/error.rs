@@ -0,0 +1,86 @@
+use frost_core::Ciphersuite;
+use std::fmt;
+
+/// Errors returned by every fallible operation in this crate
+///
+/// Variants are coarse enough to match on: a caller can retry on `Transport`
+/// or `UnknownNonces`, exclude and alert on `ShareVerification`, and treat
+/// `Codec` or `Protocol` as a bug or a misbehaving peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThresholdError {
+    /// Fewer participants than the threshold took part
+    InsufficientSigners { required: usize, available: usize },
+    /// Index that is not a valid FROST identifier for this group (0, out of range, ...)
+    InvalidIdentifier(u16),
+    /// Signer that is not registered, or has no verifying share in the public key package
+    UnknownSigner(u16),
+    /// These signers returned signature shares (or protocol messages) that failed
+    /// verification against their public data (identifiable abort)
+    ShareVerification { culprits: Vec<u16> },
+    /// Signature aggregation failed for a reason other than an invalid share
+    Aggregation(String),
+    /// Bytes that could not be encoded or decoded
+    Codec(String),
+    /// Invalid threshold parameters or inconsistent key material
+    InvalidParameters(String),
+    /// Unexpected, misrouted, duplicate or out-of-order protocol message
+    Protocol(String),
+    /// A signer answered a request with an error instead of a result
+    SignerRejected { signer_index: u16, reason: String },
+    /// A signer holds no unused nonces for its commitment in the signing package
+    /// (already used, discarded, or lost when the signer restarted)
+    UnknownNonces { signer_index: u16 },
+    /// The connection to a signer failed
+    Transport { signer_index: u16, reason: String },
+    /// A FROST key generation, refresh, resharing or repair step failed
+    KeyGeneration(String),
+    /// Keystore file could not be written, read or decrypted
+    Keystore(String),
+    /// A signing session passed its deadline before these signers answered
+    Timeout { stragglers: Vec<u16> },
+}
+
+impl fmt::Display for ThresholdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThresholdError::InsufficientSigners { required, available } => {
+                write!(f, "Not enough signers: {} < {}", available, required)
+            }
+            ThresholdError::InvalidIdentifier(index) => write!(f, "Invalid identifier: {}", index),
+            ThresholdError::UnknownSigner(index) => write!(f, "Unknown signer {}", index),
+            ThresholdError::ShareVerification { culprits } => {
+                write!(f, "Invalid signature shares from signers {:?}", culprits)
+            }
+            ThresholdError::Aggregation(msg) => write!(f, "Aggregation failed: {}", msg),
+            ThresholdError::Codec(msg) => write!(f, "Codec error: {}", msg),
+            ThresholdError::InvalidParameters(msg) => write!(f, "Invalid parameters: {}", msg),
+            ThresholdError::Protocol(msg) => write!(f, "Protocol error: {}", msg),
+            ThresholdError::SignerRejected { signer_index, reason } => {
+                write!(f, "Signer {} failed: {}", signer_index, reason)
+            }
+            ThresholdError::UnknownNonces { signer_index } => {
+                write!(f, "Signer {} has no unused nonces for this commitment", signer_index)
+            }
+            ThresholdError::Transport { signer_index, reason } => {
+                write!(f, "Connection to signer {} failed: {}", signer_index, reason)
+            }
+            ThresholdError::KeyGeneration(msg) => write!(f, "Key generation failed: {}", msg),
+            ThresholdError::Keystore(msg) => write!(f, "Keystore error: {}", msg),
+            ThresholdError::Timeout { stragglers } => {
+                write!(f, "Timed out waiting for signers {:?}", stragglers)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ThresholdError {}
+
+/// FROST identifier for a 1-based signer index
+pub(crate) fn identifier<C: Ciphersuite>(index: u16) -> Result<frost_core::Identifier<C>, ThresholdError> {
+    frost_core::Identifier::try_from(index).map_err(|_| ThresholdError::InvalidIdentifier(index))
+}
+
+/// `Codec` error for a FROST structure that failed to (de)serialize
+pub(crate) fn codec<E: fmt::Debug>(what: &str) -> impl FnOnce(E) -> ThresholdError + '_ {
+    move |e| ThresholdError::Codec(format!("{}: {:?}", what, e))
+}
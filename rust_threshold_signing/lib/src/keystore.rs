@@ -0,0 +1,559 @@
+use frost_core::{self as frost, Ciphersuite};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::{RngCore, thread_rng};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+use crate::dkg::DkgParticipant;
+use crate::error::{ThresholdError, codec, identifier};
+use crate::serialization::{read_magic_and_version, serialize, try_deserialize};
+use crate::threshold::ThresholdSigner;
+
+/// Magic bytes at the start of every keystore file
+pub const KEYSTORE_MAGIC: [u8; 4] = *b"TSKS";
+
+/// Current keystore format version; bump when the layout or crypto changes
+///
+/// Version 2 added the ciphersuite ID to the header.
+pub const KEYSTORE_VERSION: u16 = 2;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// What a keystore file contains
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeystoreKind {
+    /// A password-encrypted `KeyPackage`
+    EncryptedKeyPackage,
+    /// A `PublicKeyPackage` stored in the clear
+    PublicKeyPackage,
+    /// A password-encrypted `DkgParticipant` between two steps of a ceremony
+    EncryptedDkgState,
+}
+
+/// Argon2id cost parameters, stored in the file header so they can be raised later
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub m_cost: u32,
+    /// Number of iterations
+    pub t_cost: u32,
+    /// Degree of parallelism
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// The `argon2` crate defaults (19 MiB, 2 iterations, 1 lane)
+    fn default() -> Self {
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Highest Argon2id costs accepted (256 MiB, 16 iterations, 16 lanes)
+///
+/// The parameters come from the file header, which anyone can craft, so they
+/// are checked against this before running the KDF.
+pub const MAX_KDF_PARAMS: KdfParams = KdfParams {
+    m_cost: 256 * 1024,
+    t_cost: 16,
+    p_cost: 16,
+};
+
+/// What a keystore file says about itself, readable without its password
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeystoreInfo {
+    pub kind: KeystoreKind,
+    /// `Ciphersuite::ID` of the stored package
+    pub ciphersuite: String,
+    /// Owner of an encrypted key package or DKG state; `None` for a public key package
+    pub signer_index: Option<u16>,
+}
+
+/// Versioned header common to every keystore file
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct KeystoreHeader {
+    magic: [u8; 4],
+    version: u16,
+    kind: KeystoreKind,
+    /// Ciphersuite of the stored FROST package (`Ciphersuite::ID`)
+    ciphersuite: String,
+}
+
+impl KeystoreHeader {
+    fn new<C: Ciphersuite>(kind: KeystoreKind) -> Self {
+        Self {
+            magic: KEYSTORE_MAGIC,
+            version: KEYSTORE_VERSION,
+            kind,
+            ciphersuite: C::ID.to_string(),
+        }
+    }
+}
+
+/// Header of an encrypted key or DKG state file; authenticated as AEAD associated data
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct EncryptedKeyHeader {
+    header: KeystoreHeader,
+    signer_index: u16,
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeyFile {
+    header: EncryptedKeyHeader,
+    /// ChaCha20-Poly1305 encryption of `KeyPackage::serialize()` or `DkgParticipant::to_bytes()`
+    ciphertext: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PublicKeyFile {
+    header: KeystoreHeader,
+    /// `PublicKeyPackage::serialize()`
+    pubkey_package: Vec<u8>,
+}
+
+/// Encrypt a signer's key package with a password
+///
+/// The encryption key is derived with Argon2id from the password and a random
+/// salt; the file header (including the signer index and KDF parameters) is
+/// authenticated, so tampering with it makes decryption fail.
+pub fn encrypt_key_package<C: Ciphersuite>(
+    signer_index: u16,
+    key_package: &frost::keys::KeyPackage<C>,
+    password: &[u8],
+    kdf: KdfParams,
+) -> Result<Vec<u8>, ThresholdError> {
+    let plaintext = Zeroizing::new(
+        key_package.serialize().map_err(codec("Failed to serialize key package"))?,
+    );
+    seal::<C>(KeystoreKind::EncryptedKeyPackage, signer_index, &plaintext, password, kdf)
+}
+
+/// Decrypt a file produced by `encrypt_key_package`, returning the signer index
+/// and key package
+pub fn decrypt_key_package<C: Ciphersuite>(
+    bytes: &[u8],
+    password: &[u8],
+) -> Result<(u16, frost::keys::KeyPackage<C>), ThresholdError> {
+    let (signer_index, plaintext) = open::<C>(bytes, KeystoreKind::EncryptedKeyPackage, password)?;
+
+    let key_package = frost::keys::KeyPackage::<C>::deserialize(&plaintext)
+        .map_err(codec("Invalid key package"))?;
+
+    if *key_package.identifier() != identifier(signer_index)? {
+        return Err(ThresholdError::Keystore(format!(
+            "Key file claims signer {} but holds a different identifier",
+            signer_index
+        )));
+    }
+
+    Ok((signer_index, key_package))
+}
+
+/// Encrypt an unfinished DKG participant with a password, like a key package
+pub fn encrypt_dkg_state<C: Ciphersuite>(
+    participant: &DkgParticipant<C>,
+    password: &[u8],
+    kdf: KdfParams,
+) -> Result<Vec<u8>, ThresholdError> {
+    let plaintext = Zeroizing::new(participant.to_bytes());
+    seal::<C>(KeystoreKind::EncryptedDkgState, participant.index, &plaintext, password, kdf)
+}
+
+/// Decrypt a file produced by `encrypt_dkg_state`
+pub fn decrypt_dkg_state<C: Ciphersuite>(bytes: &[u8], password: &[u8]) -> Result<DkgParticipant<C>, ThresholdError> {
+    let (index, plaintext) = open::<C>(bytes, KeystoreKind::EncryptedDkgState, password)?;
+    let participant = DkgParticipant::<C>::from_bytes(&plaintext)?;
+
+    if participant.index != index {
+        return Err(ThresholdError::Keystore(format!(
+            "DKG state file claims participant {} but holds participant {}",
+            index, participant.index
+        )));
+    }
+
+    Ok(participant)
+}
+
+/// Serialize a public key package with a versioned header (not encrypted)
+pub fn encode_public_key_package<C: Ciphersuite>(
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+) -> Result<Vec<u8>, ThresholdError> {
+    let pubkey_package = pubkey_package.serialize()
+        .map_err(codec("Failed to serialize public key package"))?;
+
+    Ok(serialize(&PublicKeyFile {
+        header: KeystoreHeader::new::<C>(KeystoreKind::PublicKeyPackage),
+        pubkey_package,
+    }))
+}
+
+/// Decode a file produced by `encode_public_key_package`
+pub fn decode_public_key_package<C: Ciphersuite>(bytes: &[u8]) -> Result<frost::keys::PublicKeyPackage<C>, ThresholdError> {
+    check_version(bytes)?;
+    let file: PublicKeyFile = try_deserialize(bytes)?;
+    check_header::<C>(&file.header, KeystoreKind::PublicKeyPackage)?;
+
+    frost::keys::PublicKeyPackage::<C>::deserialize(&file.pubkey_package)
+        .map_err(codec("Invalid public key package"))
+}
+
+/// Write a signer's password-encrypted key package to `path`
+///
+/// On Unix the file gets mode 0600, also when it replaces an existing file.
+pub fn save_key_package<C: Ciphersuite, P: AsRef<Path>>(
+    path: P,
+    signer: &ThresholdSigner<C>,
+    password: &[u8],
+) -> Result<(), ThresholdError> {
+    let bytes = encrypt_key_package(signer.index, &signer.key_package, password, KdfParams::default())?;
+    write_private_file(path.as_ref(), &bytes)
+}
+
+/// Write the secret state of a DKG participant between two steps of a ceremony
+///
+/// The file gets mode 0600 like a key file; delete it once `finish()` has returned.
+pub fn save_dkg_state<C: Ciphersuite, P: AsRef<Path>>(
+    path: P,
+    participant: &DkgParticipant<C>,
+    password: &[u8],
+) -> Result<(), ThresholdError> {
+    let bytes = encrypt_dkg_state(participant, password, KdfParams::default())?;
+    write_private_file(path.as_ref(), &bytes)
+}
+
+/// Load a DKG participant saved with `save_dkg_state`
+pub fn load_dkg_state<C: Ciphersuite, P: AsRef<Path>>(path: P, password: &[u8]) -> Result<DkgParticipant<C>, ThresholdError> {
+    decrypt_dkg_state(&read_file(path.as_ref())?, password)
+}
+
+/// Load a signer from a key file written by `save_key_package`
+pub fn load_signer<C: Ciphersuite, P: AsRef<Path>>(path: P, password: &[u8]) -> Result<ThresholdSigner<C>, ThresholdError> {
+    let bytes = read_file(path.as_ref())?;
+    let (index, key_package) = decrypt_key_package(&bytes, password)?;
+    Ok(ThresholdSigner::new(index, key_package))
+}
+
+/// Write the group's public key package to `path` in the clear
+pub fn save_public_key_package<C: Ciphersuite, P: AsRef<Path>>(
+    path: P,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+) -> Result<(), ThresholdError> {
+    let path = path.as_ref();
+    std::fs::write(path, encode_public_key_package(pubkey_package)?)
+        .map_err(|e| ThresholdError::Keystore(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Load a public key package written by `save_public_key_package`
+pub fn load_public_key_package<C: Ciphersuite, P: AsRef<Path>>(path: P) -> Result<frost::keys::PublicKeyPackage<C>, ThresholdError> {
+    decode_public_key_package(&read_file(path.as_ref())?)
+}
+
+/// Read the header of any keystore file, whatever its kind or ciphersuite
+pub fn inspect(bytes: &[u8]) -> Result<KeystoreInfo, ThresholdError> {
+    check_version(bytes)?;
+    let (header, signer_index) = match try_deserialize::<EncryptedKeyFile>(bytes) {
+        Ok(file) => (file.header.header, Some(file.header.signer_index)),
+        Err(_) => (try_deserialize::<PublicKeyFile>(bytes)?.header, None),
+    };
+
+    let encrypted = matches!(header.kind, KeystoreKind::EncryptedKeyPackage | KeystoreKind::EncryptedDkgState);
+    if encrypted != signer_index.is_some() {
+        return Err(ThresholdError::Keystore(format!("Malformed {:?} file", header.kind)));
+    }
+
+    Ok(KeystoreInfo {
+        kind: header.kind,
+        ciphersuite: header.ciphersuite,
+        signer_index,
+    })
+}
+
+/// Encrypt `plaintext` under a key derived from `password`, authenticating the header
+fn seal<C: Ciphersuite>(
+    kind: KeystoreKind,
+    signer_index: u16,
+    plaintext: &[u8],
+    password: &[u8],
+    kdf: KdfParams,
+) -> Result<Vec<u8>, ThresholdError> {
+    let mut rng = thread_rng();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut nonce);
+
+    let header = EncryptedKeyHeader {
+        header: KeystoreHeader::new::<C>(kind),
+        signer_index,
+        kdf,
+        salt,
+        nonce,
+    };
+
+    let cipher = cipher_for(password, &header)?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload {
+            msg: plaintext,
+            aad: &serialize(&header),
+        })
+        .map_err(|_| ThresholdError::Keystore(format!("{:?} encryption failed", kind)))?;
+
+    Ok(serialize(&EncryptedKeyFile { header, ciphertext }))
+}
+
+/// Decrypt a file written by `seal`, returning the signer index from its header
+fn open<C: Ciphersuite>(
+    bytes: &[u8],
+    kind: KeystoreKind,
+    password: &[u8],
+) -> Result<(u16, Zeroizing<Vec<u8>>), ThresholdError> {
+    check_version(bytes)?;
+    let file: EncryptedKeyFile = try_deserialize(bytes)?;
+    check_header::<C>(&file.header.header, kind)?;
+
+    let cipher = cipher_for(password, &file.header)?;
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&file.header.nonce), Payload {
+            msg: &file.ciphertext,
+            aad: &serialize(&file.header),
+        })
+        .map_err(|_| ThresholdError::Keystore("Wrong password or corrupted key file".to_string()))?;
+
+    Ok((file.header.signer_index, Zeroizing::new(plaintext)))
+}
+
+/// Check magic and version before decoding the rest, whose layout depends on the version
+fn check_version(bytes: &[u8]) -> Result<(), ThresholdError> {
+    match read_magic_and_version(bytes) {
+        Some((KEYSTORE_MAGIC, KEYSTORE_VERSION)) => Ok(()),
+        Some((KEYSTORE_MAGIC, version)) => {
+            Err(ThresholdError::Keystore(format!("Unsupported keystore version {}", version)))
+        }
+        _ => Err(ThresholdError::Keystore("Not a keystore file".to_string())),
+    }
+}
+
+fn check_header<C: Ciphersuite>(header: &KeystoreHeader, expected: KeystoreKind) -> Result<(), ThresholdError> {
+    if header.kind != expected {
+        return Err(ThresholdError::Keystore(format!(
+            "Expected {:?} file, found {:?}",
+            expected, header.kind
+        )));
+    }
+    if header.ciphersuite != C::ID {
+        return Err(ThresholdError::Keystore(format!(
+            "Key file is for ciphersuite {}, expected {}",
+            header.ciphersuite,
+            C::ID
+        )));
+    }
+    Ok(())
+}
+
+fn cipher_for(password: &[u8], header: &EncryptedKeyHeader) -> Result<ChaCha20Poly1305, ThresholdError> {
+    let kdf = header.kdf;
+    if kdf.m_cost > MAX_KDF_PARAMS.m_cost || kdf.t_cost > MAX_KDF_PARAMS.t_cost || kdf.p_cost > MAX_KDF_PARAMS.p_cost {
+        return Err(ThresholdError::Keystore(format!(
+            "KDF parameters {:?} exceed the limit {:?}",
+            kdf, MAX_KDF_PARAMS
+        )));
+    }
+    let params = Params::new(header.kdf.m_cost, header.kdf.t_cost, header.kdf.p_cost, Some(32))
+        .map_err(|e| ThresholdError::Keystore(format!("Invalid KDF parameters: {}", e)))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password, &header.salt, key.as_mut())
+        .map_err(|e| ThresholdError::Keystore(format!("Key derivation failed: {}", e)))?;
+
+    Ok(ChaCha20Poly1305::new(Key::from_slice(key.as_ref())))
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, ThresholdError> {
+    std::fs::read(path).map_err(|e| ThresholdError::Keystore(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+/// Write secret bytes to `path`, readable only by the owner on Unix
+pub fn write_private_file(path: &Path, bytes: &[u8]) -> Result<(), ThresholdError> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options
+        .open(path)
+        .and_then(|mut file| {
+            // `mode` only applies when the file is created; an existing file keeps
+            // its permissions unless they are tightened before the key goes in
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+            }
+            file.write_all(bytes)
+        })
+        .map_err(|e| ThresholdError::Keystore(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::serialization::deserialize;
+    use crate::threshold::generate_frost_keys;
+
+    /// Cheap KDF parameters so tests don't spend seconds in Argon2
+    const TEST_KDF: KdfParams = KdfParams {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    #[test]
+    fn test_key_package_encryption_roundtrip() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let bytes = encrypt_key_package(2, &key_packages[1], b"correct horse", TEST_KDF).unwrap();
+        let (index, key_package) = decrypt_key_package::<E>(&bytes, b"correct horse").unwrap();
+
+        assert_eq!(index, 2);
+        assert_eq!(key_package, key_packages[1]);
+    }
+
+    #[test]
+    fn test_wrong_password_is_rejected() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let bytes = encrypt_key_package(1, &key_packages[0], b"correct horse", TEST_KDF).unwrap();
+
+        assert!(decrypt_key_package::<E>(&bytes, b"battery staple").is_err());
+    }
+
+    #[test]
+    fn test_tampered_header_is_rejected() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let bytes = encrypt_key_package(1, &key_packages[0], b"pw", TEST_KDF).unwrap();
+        let mut file: EncryptedKeyFile = deserialize(&bytes);
+        file.header.signer_index = 3;
+
+        assert!(decrypt_key_package::<E>(&serialize(&file), b"pw").is_err());
+    }
+
+    #[test]
+    fn test_excessive_kdf_params_are_rejected() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let too_costly = KdfParams { m_cost: MAX_KDF_PARAMS.m_cost + 1, ..TEST_KDF };
+        assert!(encrypt_key_package(1, &key_packages[0], b"pw", too_costly).is_err());
+
+        // A crafted header is refused before Argon2 allocates anything
+        let bytes = encrypt_key_package(1, &key_packages[0], b"pw", TEST_KDF).unwrap();
+        let mut file: EncryptedKeyFile = deserialize(&bytes);
+        file.header.kdf.m_cost = u32::MAX;
+        assert!(matches!(
+            decrypt_key_package::<E>(&serialize(&file), b"pw"),
+            Err(ThresholdError::Keystore(_))
+        ));
+    }
+
+    #[test]
+    fn test_dkg_state_encryption_roundtrip() {
+        let mut participant = DkgParticipant::<E>::new(2, 3, 2).unwrap();
+        participant.start().unwrap();
+
+        let bytes = encrypt_dkg_state(&participant, b"pw", TEST_KDF).unwrap();
+        let info = inspect(&bytes).unwrap();
+        assert_eq!((info.kind, info.signer_index), (KeystoreKind::EncryptedDkgState, Some(2)));
+
+        let restored = decrypt_dkg_state::<E>(&bytes, b"pw").unwrap();
+        assert_eq!(restored.to_bytes(), participant.to_bytes());
+        assert!(decrypt_dkg_state::<E>(&bytes, b"other").is_err());
+        // Neither kind of secret file passes for the other
+        assert!(decrypt_key_package::<E>(&bytes, b"pw").is_err());
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let key_bytes = encrypt_key_package(2, &key_packages[1], b"pw", TEST_KDF).unwrap();
+        assert!(decrypt_dkg_state::<E>(&key_bytes, b"pw").is_err());
+    }
+
+    #[test]
+    fn test_unsupported_version_is_rejected() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        let bytes = encrypt_key_package(1, &key_packages[0], b"pw", TEST_KDF).unwrap();
+        let mut file: EncryptedKeyFile = deserialize(&bytes);
+        file.header.header.version = KEYSTORE_VERSION + 1;
+        let err = decrypt_key_package::<E>(&serialize(&file), b"pw").unwrap_err();
+        assert_eq!(
+            err,
+            ThresholdError::Keystore(format!("Unsupported keystore version {}", KEYSTORE_VERSION + 1))
+        );
+
+        // A public key file is not a key file and vice versa
+        let public_bytes = encode_public_key_package(&pubkey_package).unwrap();
+        let mut public_file: PublicKeyFile = deserialize(&public_bytes);
+        public_file.header.kind = KeystoreKind::EncryptedKeyPackage;
+        assert!(decode_public_key_package::<E>(&serialize(&public_file)).is_err());
+
+        // Same layout, other ciphersuite
+        let mut public_file: PublicKeyFile = deserialize(&public_bytes);
+        public_file.header.ciphersuite = "FROST-P256-SHA256-v1".to_string();
+        assert!(decode_public_key_package::<E>(&serialize(&public_file)).is_err());
+    }
+
+    #[test]
+    fn test_keystore_files_load_into_signer() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let dir = std::env::temp_dir().join(format!("keystore-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key_path = dir.join("signer3.key");
+        let public_path = dir.join("group.pub");
+
+        let signer = ThresholdSigner::new(3, key_packages[2].clone());
+        // Overwriting a world-readable file must not leave the key readable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::write(&key_path, b"old").unwrap();
+            std::fs::set_permissions(&key_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        save_key_package(&key_path, &signer, b"pw").unwrap();
+        save_public_key_package(&public_path, &pubkey_package).unwrap();
+
+        let info = inspect(&std::fs::read(&key_path).unwrap()).unwrap();
+        assert_eq!(info.kind, KeystoreKind::EncryptedKeyPackage);
+        assert_eq!(info.ciphersuite, E::ID);
+        assert_eq!(info.signer_index, Some(3));
+        let info = inspect(&std::fs::read(&public_path).unwrap()).unwrap();
+        assert_eq!((info.kind, info.signer_index), (KeystoreKind::PublicKeyPackage, None));
+        assert!(inspect(b"not a keystore file").is_err());
+
+        let loaded = load_signer(&key_path, b"pw").unwrap();
+        assert_eq!(loaded.index, 3);
+        assert_eq!(loaded.key_package, key_packages[2]);
+        assert_eq!(load_public_key_package(&public_path).unwrap(), pubkey_package);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&key_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,39 @@
 pub mod threshold;
 pub mod serialization;
+pub mod dkg;
+pub mod transport;
+pub mod coordinator;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+pub mod reshare;
+pub mod repair;
+pub mod preprocess;
+pub mod error;
+pub mod session;
+pub mod ciphersuite;
+pub mod rerandomized;
+pub mod derivation;
+#[cfg(feature = "secp256k1-tr")]
+pub mod taproot;
+#[cfg(feature = "async")]
+pub mod async_signing;
 
-pub use threshold::{ThresholdSigner, ThresholdCoordinator, generate_frost_keys};
-pub use serialization::{SignerMessage, SignerResponse, CombinedSignature, serialize, deserialize};
+pub use threshold::{ThresholdSigner, ThresholdCoordinator, RobustSigningReport, generate_frost_keys, generate_frost_keys_dkg, refresh_frost_keys, reshare_frost_keys, repair_frost_share, find_invalid_shares};
+pub use serialization::{SignerMessage, SignerResponse, CombinedSignature, WireMessage, serialize, deserialize, try_deserialize, encode, decode};
+pub use dkg::{DkgParticipant, DkgRound1Message, DkgRound2Message};
+pub use transport::{SignerTransport, InMemoryTransport};
+#[cfg(feature = "tcp")]
+pub use transport::TcpTransport;
+pub use coordinator::PublicCoordinator;
+pub use preprocess::CommitmentPool;
+pub use reshare::{ReshareRecipient, ReshareMessage, deal_reshare};
+pub use repair::{RepairDeltaMessage, RepairSigmaMessage, repair_step1, repair_step2, repair_step3};
+pub use error::ThresholdError;
+pub use session::{SessionId, SessionState, SigningSession};
+pub use ciphersuite::Ciphersuite;
+pub use rerandomized::{RandomizedCiphersuite, Randomizer};
+pub use derivation::{ChainCode, DerivationPath, ExtendedVerifyingKey};
+#[cfg(feature = "secp256k1-tr")]
+pub use taproot::TaprootSignature;
+#[cfg(feature = "async")]
+pub use async_signing::{AsyncSignerTransport, AsyncTcpTransport, AsyncCoordinator};
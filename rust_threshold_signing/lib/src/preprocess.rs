@@ -0,0 +1,107 @@
+use frost_core::{self as frost, Ciphersuite};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Nonce commitments signers published ahead of time
+///
+/// Each commitment is handed out at most once: `take` removes it from the pool
+/// whether or not the signing attempt that uses it succeeds.
+pub struct CommitmentPool<C: Ciphersuite> {
+    commitments: BTreeMap<u16, VecDeque<frost::round1::SigningCommitments<C>>>,
+}
+
+// Derived `Default` would needlessly require `C: Default`
+impl<C: Ciphersuite> Default for CommitmentPool<C> {
+    fn default() -> Self {
+        Self {
+            commitments: BTreeMap::new(),
+        }
+    }
+}
+
+impl<C: Ciphersuite> CommitmentPool<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store commitments published by `signer_index`, to be used in order
+    pub fn add(&mut self, signer_index: u16, commitments: Vec<frost::round1::SigningCommitments<C>>) {
+        self.commitments.entry(signer_index).or_default().extend(commitments);
+    }
+
+    /// Number of unused commitments held for `signer_index`
+    pub fn available(&self, signer_index: u16) -> usize {
+        self.commitments.get(&signer_index).map_or(0, VecDeque::len)
+    }
+
+    /// Take one commitment from each of `signer_indices`
+    ///
+    /// Returns `None`, leaving the pool untouched, unless every signer has one left
+    /// and appears only once.
+    pub fn take(&mut self, signer_indices: &[u16]) -> Option<BTreeMap<u16, frost::round1::SigningCommitments<C>>> {
+        let unique: BTreeSet<u16> = signer_indices.iter().copied().collect();
+        if unique.len() != signer_indices.len() || unique.iter().any(|&idx| self.available(idx) == 0) {
+            return None;
+        }
+
+        let mut taken = BTreeMap::new();
+        for &idx in signer_indices {
+            let commitment = self.commitments.get_mut(&idx)?.pop_front()?;
+            taken.insert(idx, commitment);
+        }
+        Some(taken)
+    }
+
+    /// Drop every commitment of `signer_index` (e.g. after the signer restarted
+    /// and lost its pregenerated nonces) and return them, so the signer can be
+    /// asked to discard the matching nonces
+    pub fn clear_signer(&mut self, signer_index: u16) -> Vec<frost::round1::SigningCommitments<C>> {
+        self.commitments.remove(&signer_index).map(Vec::from).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::threshold::{ThresholdSigner, generate_frost_keys};
+
+    #[test]
+    fn test_commitment_pool_hands_out_each_commitment_once() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signer1 = ThresholdSigner::new(1, key_packages[0].clone());
+        let mut signer2 = ThresholdSigner::new(2, key_packages[1].clone());
+
+        let mut pool = CommitmentPool::new();
+        let published = signer1.preprocess_nonces(2).unwrap();
+        pool.add(1, published.clone());
+        pool.add(2, signer2.preprocess_nonces(1).unwrap());
+
+        // Signer 3 has nothing published, so nothing is taken
+        assert!(pool.take(&[1, 3]).is_none());
+        assert_eq!(pool.available(1), 2);
+
+        assert_eq!(pool.take(&[1, 2]).unwrap()[&1], published[0]);
+        assert!(pool.take(&[1, 2]).is_none());
+        assert_eq!(pool.take(&[1]).unwrap()[&1], published[1]);
+        assert_eq!(pool.available(1), 0);
+    }
+
+    #[test]
+    fn test_commitment_pool_refuses_duplicate_signers() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signer1 = ThresholdSigner::new(1, key_packages[0].clone());
+        let mut signer2 = ThresholdSigner::new(2, key_packages[1].clone());
+
+        let mut pool = CommitmentPool::new();
+        pool.add(1, signer1.preprocess_nonces(2).unwrap());
+        pool.add(2, signer2.preprocess_nonces(1).unwrap());
+
+        // Enough commitments for [1, 1, 2], but signer 1 can't sign twice
+        assert!(pool.take(&[1, 1, 2]).is_none());
+        assert_eq!(pool.available(1), 2);
+        assert_eq!(pool.available(2), 1);
+
+        assert_eq!(pool.clear_signer(1).len(), 2);
+        assert_eq!(pool.available(1), 0);
+    }
+}
@@ -0,0 +1,263 @@
+use frost_core::{self as frost, Ciphersuite, Field};
+use frost::keys::{SecretShare, VerifiableSecretSharingCommitment};
+use frost::keys::repairable::{repair_share_step_1, repair_share_step_2, repair_share_step_3};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::error::{ThresholdError, codec, identifier};
+use crate::reshare::{ScalarField, normalize_quorum, to_scalar};
+use crate::serialization::{decode, encode};
+
+/// Step 1 message: a random piece of `sender`'s weighted share, sent privately to helper `recipient`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RepairDeltaMessage {
+    pub sender: u16,
+    pub recipient: u16,
+    pub participant: u16,
+    pub delta: Vec<u8>,
+}
+
+/// Step 2 message: helper `sender`'s sum of deltas, sent privately to `participant`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RepairSigmaMessage {
+    pub sender: u16,
+    pub participant: u16,
+    pub sigma: Vec<u8>,
+}
+
+/// Step 1: Helper `helper_index` splits its share into one delta per helper
+///
+/// `helpers` lists every helper taking part (at least the threshold, including this
+/// one, excluding `participant`). No single delta reveals anything about the share.
+/// Returns the serialized `RepairDeltaMessage`s to send, keyed by recipient helper,
+/// including the one this helper keeps for itself.
+pub fn repair_step1<C: Ciphersuite>(
+    helper_index: u16,
+    key_package: &frost::keys::KeyPackage<C>,
+    helpers: &[u16],
+    participant: u16,
+) -> Result<BTreeMap<u16, Vec<u8>>, ThresholdError> {
+    let helpers = normalize_quorum(helpers)?;
+    if !helpers.contains(&helper_index) {
+        return Err(ThresholdError::InvalidParameters(format!(
+            "Helper {} is not part of the repair",
+            helper_index
+        )));
+    }
+    if helpers.contains(&participant) {
+        return Err(ThresholdError::InvalidParameters(format!(
+            "Participant {} cannot help repair its own share",
+            participant
+        )));
+    }
+    if helpers.len() < *key_package.min_signers() as usize {
+        return Err(ThresholdError::InsufficientSigners {
+            required: *key_package.min_signers() as usize,
+            available: helpers.len(),
+        });
+    }
+    if *key_package.identifier() != identifier(helper_index)? {
+        return Err(ThresholdError::InvalidParameters(format!(
+            "Key package does not belong to helper {}",
+            helper_index
+        )));
+    }
+
+    let helper_identifiers = helpers
+        .iter()
+        .map(|&i| identifier(i))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Step 1 only reads the identifier and signing share; the commitment is not needed
+    let share = SecretShare::<C>::new(
+        *key_package.identifier(),
+        *key_package.signing_share(),
+        placeholder_commitment(key_package.verifying_key())?,
+    );
+
+    let mut rng = thread_rng();
+    let mut deltas = repair_share_step_1::<C, _>(
+        &helper_identifiers,
+        &share,
+        &mut rng,
+        identifier(participant)?,
+    ).map_err(|e| ThresholdError::KeyGeneration(format!("Repair step 1 failed: {:?}", e)))?;
+
+    let mut messages = BTreeMap::new();
+    for recipient in helpers {
+        let delta = deltas
+            .remove(&identifier(recipient)?)
+            .ok_or_else(|| ThresholdError::KeyGeneration(format!("No delta computed for helper {}", recipient)))?;
+        let message = RepairDeltaMessage {
+            sender: helper_index,
+            recipient,
+            participant,
+            delta: <ScalarField<C>>::serialize(&delta).as_ref().to_vec(),
+        };
+        messages.insert(recipient, encode::<C, _>(&message));
+    }
+
+    Ok(messages)
+}
+
+/// Step 2: Helper `helper_index` sums the deltas it received from every helper
+///
+/// Returns the serialized `RepairSigmaMessage` to send to the participant.
+pub fn repair_step2<C: Ciphersuite>(
+    helper_index: u16,
+    participant: u16,
+    deltas: &[Vec<u8>],
+) -> Result<Vec<u8>, ThresholdError> {
+    let mut senders = BTreeSet::new();
+    let mut values = Vec::with_capacity(deltas.len());
+    for bytes in deltas {
+        let message: RepairDeltaMessage = decode::<C, _>(bytes)?;
+        if message.recipient != helper_index || message.participant != participant {
+            return Err(ThresholdError::Protocol(format!(
+                "Repair delta for helper {} (participant {}) delivered to helper {}",
+                message.recipient, message.participant, helper_index
+            )));
+        }
+        if !senders.insert(message.sender) {
+            return Err(ThresholdError::Protocol(format!(
+                "Duplicate repair delta from helper {}",
+                message.sender
+            )));
+        }
+        values.push(to_scalar::<C>(message.delta)?);
+    }
+
+    let sigma = repair_share_step_2::<C>(&values);
+    Ok(encode::<C, _>(&RepairSigmaMessage {
+        sender: helper_index,
+        participant,
+        sigma: <ScalarField<C>>::serialize(&sigma).as_ref().to_vec(),
+    }))
+}
+
+/// Step 3: The participant rebuilds its key package from the helpers' sigmas
+///
+/// The rebuilt signing share is checked against the participant's verifying share
+/// in `pubkey_package`, so a wrong or incomplete set of helpers is detected here.
+pub fn repair_step3<C: Ciphersuite>(
+    participant: u16,
+    min_signers: u16,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    sigmas: &[Vec<u8>],
+) -> Result<frost::keys::KeyPackage<C>, ThresholdError> {
+    let participant_identifier = identifier(participant)?;
+    let expected_share = pubkey_package
+        .verifying_shares()
+        .get(&participant_identifier)
+        .ok_or(ThresholdError::UnknownSigner(participant))?;
+
+    let mut senders = BTreeSet::new();
+    let mut values = Vec::with_capacity(sigmas.len());
+    for bytes in sigmas {
+        let message: RepairSigmaMessage = decode::<C, _>(bytes)?;
+        if message.participant != participant {
+            return Err(ThresholdError::Protocol(format!(
+                "Repair sigma for participant {} delivered to participant {}",
+                message.participant, participant
+            )));
+        }
+        if !senders.insert(message.sender) {
+            return Err(ThresholdError::Protocol(format!(
+                "Duplicate repair sigma from helper {}",
+                message.sender
+            )));
+        }
+        values.push(to_scalar::<C>(message.sigma)?);
+    }
+    if values.len() < min_signers as usize {
+        return Err(ThresholdError::InsufficientSigners {
+            required: min_signers as usize,
+            available: values.len(),
+        });
+    }
+
+    let commitment = placeholder_commitment(pubkey_package.verifying_key())?;
+    let repaired = repair_share_step_3(&values, participant_identifier, &commitment);
+
+    let verifying_share = frost::keys::VerifyingShare::<C>::from(*repaired.signing_share());
+    if verifying_share != *expected_share {
+        return Err(ThresholdError::KeyGeneration(format!(
+            "Repaired share of participant {} does not match its verifying share",
+            participant
+        )));
+    }
+
+    Ok(frost::keys::KeyPackage::<C>::new(
+        participant_identifier,
+        *repaired.signing_share(),
+        verifying_share,
+        *pubkey_package.verifying_key(),
+        min_signers,
+    ))
+}
+
+/// Constant-only commitment to the group key
+///
+/// `KeyPackage` does not keep the dealer's VSS commitment, and the repair steps
+/// never verify against it, so only the group key is committed to.
+fn placeholder_commitment<C: Ciphersuite>(verifying_key: &frost::VerifyingKey<C>) -> Result<VerifiableSecretSharingCommitment<C>, ThresholdError> {
+    let group_key = verifying_key.serialize().map_err(codec("Invalid verifying key"))?;
+    VerifiableSecretSharingCommitment::<C>::deserialize(vec![group_key]).map_err(codec("Invalid commitment"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::threshold::generate_frost_keys;
+
+    #[test]
+    fn test_repair_step1_rejects_bad_helper_set() {
+        let (key_packages, _) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        // Too few helpers, participant among the helpers, helper not in the set
+        assert!(repair_step1(1, &key_packages[0], &[1, 2], 5).is_err());
+        assert!(repair_step1(1, &key_packages[0], &[1, 2, 5], 5).is_err());
+        assert!(repair_step1(4, &key_packages[3], &[1, 2, 3], 5).is_err());
+        assert!(repair_step1(1, &key_packages[0], &[1, 2, 3], 5).is_ok());
+    }
+
+    #[test]
+    fn test_repair_step2_rejects_misrouted_delta() {
+        let (key_packages, _) = generate_frost_keys::<E>(5, 3).unwrap();
+        let helpers = [1, 2, 3];
+
+        let outgoing = repair_step1(1, &key_packages[0], &helpers, 5).unwrap();
+
+        assert!(repair_step2::<E>(2, 5, &[outgoing[&3].clone()]).is_err());
+        assert!(repair_step2::<E>(2, 4, &[outgoing[&2].clone()]).is_err());
+        assert!(repair_step2::<E>(2, 5, &[outgoing[&2].clone(), outgoing[&2].clone()]).is_err());
+    }
+
+    #[test]
+    fn test_repair_step3_requires_enough_sigmas() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+        let helpers = [1, 2, 3];
+
+        let mut inboxes: BTreeMap<u16, Vec<Vec<u8>>> = BTreeMap::new();
+        for helper in helpers {
+            for (recipient, bytes) in repair_step1(helper, &key_packages[helper as usize - 1], &helpers, 5).unwrap() {
+                inboxes.entry(recipient).or_default().push(bytes);
+            }
+        }
+        let sigmas: Vec<Vec<u8>> = helpers
+            .iter()
+            .map(|helper| repair_step2::<E>(*helper, 5, &inboxes[helper]).unwrap())
+            .collect();
+
+        assert_eq!(
+            repair_step3(5, 3, &pubkey_package, &sigmas[..2]),
+            Err(ThresholdError::InsufficientSigners { required: 3, available: 2 })
+        );
+        // Sigmas computed for participant 5 are refused by participant 4
+        assert!(repair_step3(4, 3, &pubkey_package, &sigmas).is_err());
+
+        let repaired = repair_step3(5, 3, &pubkey_package, &sigmas).unwrap();
+        assert_eq!(repaired, key_packages[4]);
+    }
+}
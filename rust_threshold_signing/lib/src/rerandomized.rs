@@ -0,0 +1,176 @@
+//! Rerandomized FROST: each signature under a fresh key derived from the group key
+//!
+//! For every signing the coordinator picks a random scalar `α` (the randomizer)
+//! and the signature verifies under `group key + α·G` instead of the group key.
+//! Without `α`, signatures cannot be linked to each other or to the group key.
+//! The randomizer reaches the signers with the signing package, in
+//! `SignerMessage::RandomizedRound2Request`; anyone who learns it can link that
+//! one signature, so only send it over confidential channels.
+
+use frost_core::keys::{KeyPackage, PublicKeyPackage, SigningShare, VerifyingShare};
+use frost_core::{Ciphersuite, SigningPackage};
+use frost_rerandomized::RandomizedParams;
+use rand::thread_rng;
+
+pub use frost_rerandomized::{RandomizedCiphersuite, Randomizer};
+
+use crate::error::{ThresholdError, codec};
+use crate::reshare::to_scalar;
+
+/// Fresh randomizer for one signing, bound to its serialized `signing_package`
+pub fn generate_randomizer<C: RandomizedCiphersuite>(signing_package: &[u8]) -> Result<Randomizer<C>, ThresholdError> {
+    let signing_package = SigningPackage::<C>::deserialize(signing_package).map_err(codec("Invalid signing package"))?;
+    Randomizer::new(thread_rng(), &signing_package).map_err(codec("Failed to derive randomizer"))
+}
+
+/// The group verifying key rerandomized by `randomizer`
+pub fn randomized_verifying_key<C: Ciphersuite>(
+    pubkey_package: &PublicKeyPackage<C>,
+    randomizer: &Randomizer<C>,
+) -> frost_core::VerifyingKey<C> {
+    *RandomizedParams::from_randomizer(pubkey_package.verifying_key(), *randomizer).randomized_verifying_key()
+}
+
+/// Public key package whose verifying key and shares are all shifted by `α·G`
+///
+/// Shares from a rerandomized signing verify, and aggregate, against this package.
+pub(crate) fn randomize_public_key_package<C: Ciphersuite>(
+    pubkey_package: &PublicKeyPackage<C>,
+    randomizer: &Randomizer<C>,
+) -> PublicKeyPackage<C> {
+    let params = RandomizedParams::from_randomizer(pubkey_package.verifying_key(), *randomizer);
+    let verifying_shares = pubkey_package
+        .verifying_shares()
+        .iter()
+        .map(|(id, share)| (*id, VerifyingShare::new(share.to_element() + *params.randomizer_element())))
+        .collect();
+    PublicKeyPackage::new(verifying_shares, *params.randomized_verifying_key())
+}
+
+/// Key package that signs under the group key rerandomized by `randomizer`
+///
+/// `α` is added to the signing share; since Lagrange coefficients sum to one,
+/// the aggregate then signs with `secret + α`. This is what
+/// `frost_rerandomized::sign` does internally, kept here so signers work with
+/// any `Ciphersuite`.
+pub(crate) fn randomize_key_package<C: Ciphersuite>(
+    key_package: &KeyPackage<C>,
+    randomizer: &Randomizer<C>,
+) -> Result<KeyPackage<C>, ThresholdError> {
+    let params = RandomizedParams::from_randomizer(key_package.verifying_key(), *randomizer);
+    let alpha = to_scalar::<C>(randomizer.serialize())?;
+    Ok(KeyPackage::new(
+        *key_package.identifier(),
+        SigningShare::new(key_package.signing_share().to_scalar() + alpha),
+        VerifyingShare::new(key_package.verifying_share().to_element() + *params.randomizer_element()),
+        *params.randomized_verifying_key(),
+        *key_package.min_signers(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::coordinator::PublicCoordinator;
+    use crate::serialization::{CombinedSignature, decode, encode};
+    use crate::threshold::{ThresholdCoordinator, ThresholdSigner, generate_frost_keys};
+    use crate::transport::InMemoryTransport;
+
+    fn coordinator(max_signers: u16, min_signers: u16) -> ThresholdCoordinator<E> {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(max_signers, min_signers).unwrap();
+        let signers = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        ThresholdCoordinator::new(min_signers, signers, pubkey_package)
+    }
+
+    #[test]
+    fn test_randomized_signatures_use_unlinkable_keys() {
+        let mut coordinator = coordinator(5, 3);
+        let group_key = coordinator.pubkey_package.verifying_key().serialize().unwrap();
+        let message = b"private payment";
+
+        let first = coordinator.perform_randomized_threshold_signing(message, vec![1, 2, 3], None).unwrap();
+        let second = coordinator.perform_randomized_threshold_signing(message, vec![1, 2, 3], None).unwrap();
+
+        for signature in [&first, &second] {
+            assert_eq!(signature.public_key, group_key);
+            let randomized_key = signature.randomized_public_key.as_ref().unwrap();
+            assert_ne!(*randomized_key, group_key);
+            assert!(signature.verify::<E>(message));
+
+            // The signature is only valid under the randomized key
+            let under_group_key = CombinedSignature {
+                randomized_public_key: None,
+                ..signature.clone()
+            };
+            assert!(!under_group_key.verify::<E>(message));
+
+            let decoded: CombinedSignature = decode::<E, _>(&encode::<E, _>(signature)).unwrap();
+            assert_eq!(&decoded, signature);
+        }
+        assert_ne!(first.randomized_public_key, second.randomized_public_key);
+    }
+
+    #[test]
+    fn test_randomized_signing_with_given_randomizer() {
+        let mut coordinator = coordinator(3, 2);
+        let randomizer = Randomizer::<E>::deserialize(&[7u8; 32]).unwrap();
+        let expected_key = randomized_verifying_key(&coordinator.pubkey_package, &randomizer)
+            .serialize()
+            .unwrap();
+
+        let signature = coordinator
+            .perform_randomized_threshold_signing(b"message", vec![1, 3], Some(randomizer))
+            .unwrap();
+        assert_eq!(signature.randomized_public_key, Some(expected_key));
+        assert!(signature.verify::<E>(b"message"));
+    }
+
+    #[test]
+    fn test_public_coordinator_randomizes_preprocessed_signing() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut coordinator = PublicCoordinator::new(2, pubkey_package);
+        for (i, kp) in key_packages.into_iter().enumerate() {
+            let signer = ThresholdSigner::new((i + 1) as u16, kp);
+            coordinator.add_signer(Box::new(InMemoryTransport::new(signer))).unwrap();
+        }
+        coordinator.preprocess(1).unwrap();
+
+        // One signing from the preprocessed pool, one with both rounds
+        for _ in 0..2 {
+            let signature = coordinator
+                .perform_randomized_threshold_signing(b"message", vec![1, 2], None)
+                .unwrap();
+            assert!(signature.randomized_public_key.is_some());
+            assert!(signature.verify::<E>(b"message"));
+        }
+        assert_eq!(coordinator.preprocessed_commitments(1), 0);
+    }
+
+    #[test]
+    fn test_signer_refuses_malformed_randomizer() {
+        let mut coordinator = coordinator(3, 2);
+        let signing_package = {
+            let signer = &mut coordinator.signers[0];
+            let commitments = signer.round1_generate_nonces();
+            let mut all = std::collections::BTreeMap::new();
+            all.insert(*signer.key_package.identifier(), commitments);
+            SigningPackage::new(all, b"message").serialize().unwrap()
+        };
+
+        let request = crate::serialization::SignerMessage::RandomizedRound2Request {
+            signer_index: 1,
+            signing_package,
+            randomizer: vec![0xff; 32],
+        };
+        let response = coordinator.signers[0].receive_serialized_signing_request(&encode::<E, _>(&request));
+        assert!(matches!(
+            decode::<E, _>(&response),
+            Ok(crate::serialization::SignerResponse::Error { .. })
+        ));
+    }
+}
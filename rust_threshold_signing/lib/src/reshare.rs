@@ -0,0 +1,407 @@
+use frost_core::{self as frost, Ciphersuite, Element, Field, Group, Scalar};
+use frost::keys::{IdentifierList, SecretShare, VerifiableSecretSharingCommitment};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::error::{ThresholdError, codec, identifier};
+use crate::serialization::{decode, encode};
+
+pub(crate) type ScalarField<C> = <<C as Ciphersuite>::Group as Group>::Field;
+
+/// Sub-share dealt privately by old holder `sender` to new committee member `recipient`
+///
+/// `share` carries the dealer's polynomial commitment, whose constant term must be
+/// the dealer's Lagrange-weighted verifying share from the old public key package.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(bound = "C: Ciphersuite")]
+pub struct ReshareMessage<C: Ciphersuite> {
+    pub sender: u16,
+    pub recipient: u16,
+    pub share: SecretShare<C>,
+}
+
+/// Deal the share of old holder `dealer_index` to a new `(max_signers, min_signers)` committee
+///
+/// `quorum` lists the old holders taking part (at least the old threshold, including
+/// the dealer). The dealer weights its signing share by its Lagrange coefficient for
+/// the quorum and splits the result with a fresh polynomial of degree `min_signers - 1`,
+/// so the sub-shares of the whole quorum add up to shares of the unchanged group secret.
+///
+/// Returns the serialized `ReshareMessage`s to send, keyed by recipient index.
+pub fn deal_reshare<C: Ciphersuite>(
+    dealer_index: u16,
+    key_package: &frost::keys::KeyPackage<C>,
+    quorum: &[u16],
+    max_signers: u16,
+    min_signers: u16,
+) -> Result<BTreeMap<u16, Vec<u8>>, ThresholdError> {
+    let quorum = normalize_quorum(quorum)?;
+    if !quorum.contains(&dealer_index) {
+        return Err(ThresholdError::InvalidParameters(format!(
+            "Dealer {} is not part of the resharing quorum",
+            dealer_index
+        )));
+    }
+    if quorum.len() < *key_package.min_signers() as usize {
+        return Err(ThresholdError::InsufficientSigners {
+            required: *key_package.min_signers() as usize,
+            available: quorum.len(),
+        });
+    }
+    if *key_package.identifier() != identifier(dealer_index)? {
+        return Err(ThresholdError::InvalidParameters(format!(
+            "Key package does not belong to dealer {}",
+            dealer_index
+        )));
+    }
+
+    let share = to_scalar::<C>(key_package.signing_share().serialize())?;
+    let weighted = share * lagrange_at_zero::<C>(dealer_index, &quorum)?;
+    let key = frost::SigningKey::<C>::from_scalar(weighted)
+        .map_err(|e| ThresholdError::KeyGeneration(format!("Invalid weighted share: {:?}", e)))?;
+
+    let mut rng = thread_rng();
+    let (mut shares, _) = frost::keys::split(&key, max_signers, min_signers, IdentifierList::Default, &mut rng)
+        .map_err(|e| ThresholdError::KeyGeneration(format!("Resharing split failed: {:?}", e)))?;
+
+    let mut messages = BTreeMap::new();
+    for recipient in 1..=max_signers {
+        let share = shares
+            .remove(&identifier(recipient)?)
+            .ok_or_else(|| ThresholdError::KeyGeneration(format!("No sub-share dealt to participant {}", recipient)))?;
+        let message = ReshareMessage {
+            sender: dealer_index,
+            recipient,
+            share,
+        };
+        messages.insert(recipient, encode::<C, _>(&message));
+    }
+
+    Ok(messages)
+}
+
+/// One member of the new committee in a resharing
+///
+/// Collects one `ReshareMessage` from every dealer in the quorum, checking each
+/// sub-share against its commitment and the dealer's old verifying share as it
+/// arrives. Each member only sees the commitments sent to it, so members must
+/// compare the `PublicKeyPackage` returned by `finish()` before using the new keys.
+pub struct ReshareRecipient<C: Ciphersuite> {
+    pub index: u16,
+    pub max_signers: u16,
+    pub min_signers: u16,
+    identifier: frost::Identifier<C>,
+    old_pubkey_package: frost::keys::PublicKeyPackage<C>,
+    quorum: Vec<u16>,
+    received: BTreeMap<u16, SecretShare<C>>,
+}
+
+impl<C: Ciphersuite> ReshareRecipient<C> {
+    pub fn new(
+        index: u16,
+        max_signers: u16,
+        min_signers: u16,
+        old_pubkey_package: frost::keys::PublicKeyPackage<C>,
+        quorum: &[u16],
+    ) -> Result<Self, ThresholdError> {
+        if index == 0 || index > max_signers {
+            return Err(ThresholdError::InvalidIdentifier(index));
+        }
+        if min_signers < 2 || min_signers > max_signers {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "Invalid resharing parameters: {}-of-{}",
+                min_signers, max_signers
+            )));
+        }
+
+        let quorum = normalize_quorum(quorum)?;
+        for dealer in &quorum {
+            if !old_pubkey_package.verifying_shares().contains_key(&identifier(*dealer)?) {
+                return Err(ThresholdError::UnknownSigner(*dealer));
+            }
+        }
+
+        Ok(Self {
+            index,
+            max_signers,
+            min_signers,
+            identifier: identifier(index)?,
+            old_pubkey_package,
+            quorum,
+            received: BTreeMap::new(),
+        })
+    }
+
+    /// Store sub-shares addressed to this participant
+    ///
+    /// May be called several times as messages arrive; a message that fails
+    /// verification is rejected without affecting the ones already stored.
+    pub fn receive(&mut self, messages: &[Vec<u8>]) -> Result<(), ThresholdError> {
+        for bytes in messages {
+            let message: ReshareMessage<C> = decode::<C, _>(bytes)?;
+
+            if message.recipient != self.index || *message.share.identifier() != self.identifier {
+                return Err(ThresholdError::Protocol(format!(
+                    "Resharing message for participant {} delivered to participant {}",
+                    message.recipient, self.index
+                )));
+            }
+            if !self.quorum.contains(&message.sender) {
+                return Err(ThresholdError::Protocol(format!(
+                    "Resharing message from {}, who is not a dealer",
+                    message.sender
+                )));
+            }
+            if self.received.contains_key(&message.sender) {
+                return Err(ThresholdError::Protocol(format!(
+                    "Duplicate resharing message from dealer {}",
+                    message.sender
+                )));
+            }
+
+            self.verify_sub_share(message.sender, &message.share)?;
+            self.received.insert(message.sender, message.share);
+        }
+        Ok(())
+    }
+
+    /// Combine the sub-shares of every dealer into this participant's new key package
+    ///
+    /// Fails if a dealer is missing or the quorum did not reconstruct the old group key
+    /// (for instance because it was smaller than the old threshold).
+    pub fn finish(&self) -> Result<(frost::keys::KeyPackage<C>, frost::keys::PublicKeyPackage<C>), ThresholdError> {
+        if let Some(missing) = self.quorum.iter().find(|dealer| !self.received.contains_key(dealer)) {
+            return Err(ThresholdError::Protocol(format!(
+                "Participant {} is missing the sub-share of dealer {}",
+                self.index, missing
+            )));
+        }
+
+        let mut signing_share = <ScalarField<C>>::zero();
+        for share in self.received.values() {
+            signing_share = signing_share + to_scalar::<C>(share.signing_share().serialize())?;
+        }
+        let commitments: Vec<_> = self.received.values().map(|share| share.commitment()).collect();
+        let group_commitment = sum_commitments(&commitments)?;
+
+        let identifiers = (1..=self.max_signers)
+            .map(identifier::<C>)
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        let pubkey_package = frost::keys::PublicKeyPackage::<C>::from_commitment(&identifiers, &group_commitment)
+            .map_err(|e| ThresholdError::KeyGeneration(format!("Failed to derive public key package: {:?}", e)))?;
+
+        if pubkey_package.verifying_key() != self.old_pubkey_package.verifying_key() {
+            return Err(ThresholdError::KeyGeneration(
+                "Resharing quorum did not reconstruct the group verifying key".to_string(),
+            ));
+        }
+
+        let signing_share = frost::keys::SigningShare::<C>::deserialize(
+            <ScalarField<C>>::serialize(&signing_share).as_ref(),
+        ).map_err(codec("Invalid signing share"))?;
+        let verifying_share = frost::keys::VerifyingShare::<C>::from(signing_share);
+        if pubkey_package.verifying_shares().get(&self.identifier) != Some(&verifying_share) {
+            return Err(ThresholdError::KeyGeneration(format!(
+                "Signing share of participant {} does not match the public key package",
+                self.index
+            )));
+        }
+
+        let key_package = frost::keys::KeyPackage::<C>::new(
+            self.identifier,
+            signing_share,
+            verifying_share,
+            *pubkey_package.verifying_key(),
+            self.min_signers,
+        );
+
+        Ok((key_package, pubkey_package))
+    }
+
+    /// Check a sub-share against its commitment, and the commitment against the
+    /// dealer's Lagrange-weighted old verifying share
+    ///
+    /// A sub-share that fails either check names its dealer in `ShareVerification`.
+    fn verify_sub_share(&self, dealer: u16, share: &SecretShare<C>) -> Result<(), ThresholdError> {
+        let culprit = || ThresholdError::ShareVerification { culprits: vec![dealer] };
+
+        // Wrong polynomial degree
+        if share.commitment().serialize().map(|c| c.len()).unwrap_or_default() != self.min_signers as usize {
+            return Err(culprit());
+        }
+
+        // Sub-share does not match its commitment
+        let (_, dealt_key) = share.verify().map_err(|_| culprit())?;
+
+        let old_share = self.old_pubkey_package
+            .verifying_shares()
+            .get(&identifier(dealer)?)
+            .ok_or(ThresholdError::UnknownSigner(dealer))?;
+        let old_share = to_element::<C>(old_share.serialize().map_err(codec("Invalid verifying share"))?)?;
+        let expected = old_share * lagrange_at_zero::<C>(dealer, &self.quorum)?;
+
+        // Dealer did not reshare its own key share
+        let dealt_key = dealt_key.serialize().map_err(codec("Invalid verifying key"))?;
+        let expected = C::Group::serialize(&expected).map_err(codec("Invalid element"))?;
+        if dealt_key.as_slice() != expected.as_ref() {
+            return Err(culprit());
+        }
+        Ok(())
+    }
+}
+
+/// Sort the quorum and reject duplicates
+pub(crate) fn normalize_quorum(quorum: &[u16]) -> Result<Vec<u16>, ThresholdError> {
+    let unique: BTreeSet<u16> = quorum.iter().copied().collect();
+    if unique.len() != quorum.len() {
+        return Err(ThresholdError::InvalidParameters(
+            "Resharing quorum contains duplicate participants".to_string(),
+        ));
+    }
+    Ok(unique.into_iter().collect())
+}
+
+/// Lagrange coefficient of `index` for interpolating at zero over `quorum`
+fn lagrange_at_zero<C: Ciphersuite>(index: u16, quorum: &[u16]) -> Result<Scalar<C>, ThresholdError> {
+    let x_i = identifier_scalar::<C>(index)?;
+    let mut numerator = <ScalarField<C>>::one();
+    let mut denominator = <ScalarField<C>>::one();
+    for &other in quorum.iter().filter(|&&other| other != index) {
+        let x_j = identifier_scalar::<C>(other)?;
+        numerator = numerator * x_j;
+        denominator = denominator * (x_j - x_i);
+    }
+    let inverse = <ScalarField<C>>::invert(&denominator)
+        .map_err(|_| ThresholdError::InvalidParameters("Resharing quorum contains duplicate participants".to_string()))?;
+    Ok(numerator * inverse)
+}
+
+/// Add the dealers' commitments coefficient by coefficient
+fn sum_commitments<C: Ciphersuite>(
+    commitments: &[&VerifiableSecretSharingCommitment<C>],
+) -> Result<VerifiableSecretSharingCommitment<C>, ThresholdError> {
+    let mut sum: Vec<Element<C>> = Vec::new();
+    for commitment in commitments {
+        let coefficients = VerifiableSecretSharingCommitment::<C>::serialize(commitment).map_err(codec("Invalid commitment"))?;
+        if sum.is_empty() {
+            sum = vec![C::Group::identity(); coefficients.len()];
+        }
+        if coefficients.len() != sum.len() {
+            return Err(ThresholdError::KeyGeneration("Resharing commitments have different degrees".to_string()));
+        }
+        for (total, coefficient) in sum.iter_mut().zip(coefficients) {
+            *total = *total + to_element::<C>(coefficient)?;
+        }
+    }
+
+    let serialized = sum
+        .iter()
+        .map(|element| C::Group::serialize(element).map(|bytes| bytes.as_ref().to_vec()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(codec("Invalid commitment sum"))?;
+    VerifiableSecretSharingCommitment::<C>::deserialize(serialized).map_err(codec("Invalid commitment sum"))
+}
+
+fn identifier_scalar<C: Ciphersuite>(index: u16) -> Result<Scalar<C>, ThresholdError> {
+    to_scalar::<C>(identifier::<C>(index)?.serialize())
+}
+
+pub(crate) fn to_scalar<C: Ciphersuite>(bytes: Vec<u8>) -> Result<Scalar<C>, ThresholdError> {
+    let bytes: <ScalarField<C> as Field>::Serialization = bytes
+        .try_into()
+        .map_err(|_| ThresholdError::Codec("Invalid scalar length".to_string()))?;
+    <ScalarField<C>>::deserialize(&bytes).map_err(codec("Invalid scalar"))
+}
+
+fn to_element<C: Ciphersuite>(bytes: Vec<u8>) -> Result<Element<C>, ThresholdError> {
+    let bytes: <C::Group as Group>::Serialization = bytes
+        .try_into()
+        .map_err(|_| ThresholdError::Codec("Invalid element length".to_string()))?;
+    C::Group::deserialize(&bytes).map_err(codec("Invalid element"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::threshold::generate_frost_keys;
+
+    #[test]
+    fn test_reshare_recipients_reach_same_group_key() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+        let quorum = [1, 3, 4];
+
+        let mut recipients: Vec<ReshareRecipient<E>> = (1..=4)
+            .map(|i| ReshareRecipient::new(i, 4, 2, pubkey_package.clone(), &quorum).unwrap())
+            .collect();
+        for dealer in quorum {
+            let outgoing = deal_reshare(dealer, &key_packages[dealer as usize - 1], &quorum, 4, 2).unwrap();
+            assert_eq!(outgoing.len(), 4);
+            for (recipient, bytes) in outgoing {
+                recipients[recipient as usize - 1].receive(&[bytes]).unwrap();
+            }
+        }
+
+        let mut pubkey_packages = Vec::new();
+        for recipient in &recipients {
+            let (key_package, new_pubkey_package) = recipient.finish().unwrap();
+            assert_eq!(key_package.verifying_key(), pubkey_package.verifying_key());
+            assert_eq!(*key_package.min_signers(), 2);
+            pubkey_packages.push(new_pubkey_package);
+        }
+        assert!(pubkey_packages.windows(2).all(|w| w[0] == w[1]));
+    }
+
+    #[test]
+    fn test_deal_reshare_rejects_bad_quorum() {
+        let (key_packages, _) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        // Dealer outside the quorum, quorum below the threshold, duplicate dealer
+        assert!(deal_reshare(2, &key_packages[1], &[1, 3, 4], 4, 2).is_err());
+        assert!(deal_reshare(1, &key_packages[0], &[1, 3], 4, 2).is_err());
+        assert!(deal_reshare(1, &key_packages[0], &[1, 1, 3], 4, 2).is_err());
+        // Key package of another participant
+        assert!(deal_reshare(1, &key_packages[1], &[1, 2, 3], 4, 2).is_err());
+    }
+
+    #[test]
+    fn test_reshare_recipient_rejects_dealer_with_wrong_share() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let (other_key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let quorum = [1, 2];
+
+        // Dealer 2 reshares a share that is not the one in the public key package
+        let forged = frost::keys::KeyPackage::new(
+            *key_packages[1].identifier(),
+            *other_key_packages[1].signing_share(),
+            *key_packages[1].verifying_share(),
+            *key_packages[1].verifying_key(),
+            *key_packages[1].min_signers(),
+        );
+
+        let mut recipient = ReshareRecipient::new(1, 3, 2, pubkey_package, &quorum).unwrap();
+        let honest = deal_reshare(1, &key_packages[0], &quorum, 3, 2).unwrap();
+        let dishonest = deal_reshare(2, &forged, &quorum, 3, 2).unwrap();
+
+        recipient.receive(&[honest[&1].clone()]).unwrap();
+        assert_eq!(
+            recipient.receive(&[dishonest[&1].clone()]),
+            Err(ThresholdError::ShareVerification { culprits: vec![2] })
+        );
+        assert!(recipient.finish().is_err());
+    }
+
+    #[test]
+    fn test_reshare_recipient_rejects_misrouted_and_duplicate_messages() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let quorum = [1, 2];
+
+        let mut recipient = ReshareRecipient::new(1, 3, 2, pubkey_package, &quorum).unwrap();
+        let outgoing = deal_reshare(1, &key_packages[0], &quorum, 3, 2).unwrap();
+
+        assert!(recipient.receive(&[outgoing[&2].clone()]).is_err());
+        recipient.receive(&[outgoing[&1].clone()]).unwrap();
+        assert!(recipient.receive(&[outgoing[&1].clone()]).is_err());
+    }
+}
@@ -1,44 +1,322 @@
+use bincode::Options;
+use frost_core::Ciphersuite;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::error::ThresholdError;
+use crate::dkg::{DkgRound1Message, DkgRound2Message};
+use crate::repair::{RepairDeltaMessage, RepairSigmaMessage};
+use crate::reshare::ReshareMessage;
+
+/// Largest encoding `try_deserialize` accepts; matches `transport::MAX_FRAME_SIZE`
+pub const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
+
+/// Magic bytes at the start of every envelope
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"TSMG";
+
+/// Current envelope format version; bump when the envelope or any message layout changes
+///
+/// Version 2 made `CombinedSignature` variable-length for other ciphersuites.
+/// Version 3 added `CombinedSignature::randomized_public_key`.
+pub const ENVELOPE_VERSION: u16 = 3;
+
+/// Which structure an envelope carries
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    SignerMessage,
+    SignerResponse,
+    CombinedSignature,
+    DkgRound1,
+    DkgRound2,
+    Reshare,
+    RepairDelta,
+    RepairSigma,
+    TaprootSignature,
+}
+
+/// A structure exchanged between parties, always sent inside an envelope
+///
+/// See `encode` and `decode`.
+pub trait WireMessage: Serialize + DeserializeOwned {
+    const MESSAGE_TYPE: MessageType;
+}
+
+/// Self-describing wrapper around every serialized `WireMessage`
+///
+/// `magic` and `version` come first and have a fixed size, so a peer running
+/// another protocol version gets a version error rather than a decoding error.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct Envelope {
+    magic: [u8; 4],
+    version: u16,
+    message_type: MessageType,
+    ciphersuite: String,
+    payload: Vec<u8>,
+}
+
+/// Request sent from the coordinator to a signer
+///
+/// FROST structures are carried in their own canonical encoding
+/// (`frost::SigningPackage::serialize()` etc.), so any signer speaking this
+/// protocol can decode them regardless of where it runs.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
-pub struct SignerMessage {
-    pub signer_index: u8,
-    pub message_hash: [u8; 32],
-    pub nonce_commitment: [u8; 32],
+pub enum SignerMessage {
+    /// Round 1: ask the signer for fresh nonce commitments
+    Round1Request { signer_index: u16 },
+    /// Round 2: ask the signer to sign a serialized `frost::SigningPackage`
+    Round2Request {
+        signer_index: u16,
+        signing_package: Vec<u8>,
+    },
+    /// Preprocessing: ask the signer to pregenerate `count` nonces and publish
+    /// their commitments, so later signing needs only the round 2 request
+    PreprocessRequest { signer_index: u16, count: u16 },
+    /// Batch round 2: sign several serialized `frost::SigningPackage`s at once,
+    /// then drop the batch nonces none of them used (an empty request only drops them)
+    BatchRound2Request {
+        signer_index: u16,
+        signing_packages: Vec<Vec<u8>>,
+    },
+    /// Round 2 of rerandomized signing: sign a serialized `frost::SigningPackage`
+    /// under the group key rerandomized by the serialized `Randomizer`
+    RandomizedRound2Request {
+        signer_index: u16,
+        signing_package: Vec<u8>,
+        randomizer: Vec<u8>,
+    },
+    /// Round 2 of signing at a derivation path: sign a serialized
+    /// `frost::SigningPackage` with the share tweaked for the child key at `path`
+    /// under `chain_code` (see `derivation`)
+    DerivedRound2Request {
+        signer_index: u16,
+        signing_package: Vec<u8>,
+        chain_code: [u8; 32],
+        path: Vec<u32>,
+    },
+    /// Drop the preprocessed nonces behind these serialized
+    /// `frost::round1::SigningCommitments` unused, e.g. after a failed signing
+    /// attempt the coordinator took them for
+    DiscardNoncesRequest {
+        signer_index: u16,
+        commitments: Vec<Vec<u8>>,
+    },
+    /// Batch round 1: ask the signer for `count` fresh nonce commitments, one per
+    /// message of a batch, held apart from the preprocessed nonces
+    BatchRound1Request { signer_index: u16, count: u16 },
 }
 
+/// Response sent from a signer back to the coordinator
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
-pub struct SignerResponse {
-    pub signer_index: u8,
-    pub signature_share: [u8; 32],
-    pub nonce_share: [u8; 32],
+pub enum SignerResponse {
+    /// Round 1: serialized `frost::round1::SigningCommitments`
+    Commitments {
+        signer_index: u16,
+        commitments: Vec<u8>,
+    },
+    /// Round 2: serialized `frost::round2::SignatureShare`
+    SignatureShare {
+        signer_index: u16,
+        signature_share: Vec<u8>,
+    },
+    /// The signer could not process the request
+    Error { signer_index: u16, reason: String },
+    /// Preprocessing or batch round 1: one serialized
+    /// `frost::round1::SigningCommitments` per pregenerated nonce
+    PreprocessedCommitments {
+        signer_index: u16,
+        commitments: Vec<Vec<u8>>,
+    },
+    /// Batch round 2: one encoded `SignatureShare` or `Error` response per
+    /// signing package, in request order
+    BatchSignatureShares {
+        signer_index: u16,
+        responses: Vec<Vec<u8>>,
+    },
+    /// Discarding: how many of the listed nonces the signer still held and dropped
+    NoncesDiscarded { signer_index: u16, discarded: u16 },
+    /// Round 2: the signer holds no unused nonces for its commitment in the
+    /// signing package, so the coordinator's commitments for it are stale
+    UnknownNonces { signer_index: u16 },
 }
 
+/// Aggregated signature and the verifying key it was signed for (the group key,
+/// or a child key from `derivation`), in the ciphersuite's own encoding (64 and
+/// 32 bytes for Ed25519)
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct CombinedSignature {
-    #[serde(with = "serde_big_array::BigArray")]
-    pub signature: [u8; 64],
-    pub public_key: [u8; 32],
+    pub signature: Vec<u8>,
+    pub public_key: Vec<u8>,
+    /// For rerandomized signing, the one-time key the signature verifies under
+    /// instead of `public_key` (see `rerandomized`)
+    pub randomized_public_key: Option<Vec<u8>>,
+}
+
+impl CombinedSignature {
+    /// Key the signature verifies under: the randomized key if there is one,
+    /// otherwise `public_key`
+    pub fn verifying_key(&self) -> &[u8] {
+        self.randomized_public_key.as_deref().unwrap_or(&self.public_key)
+    }
+
+    /// Check the signature on `message` against `verifying_key()`
+    ///
+    /// Malformed bytes count as an invalid signature.
+    pub fn verify<C: Ciphersuite>(&self, message: &[u8]) -> bool {
+        let Ok(verifying_key) = frost_core::VerifyingKey::<C>::deserialize(self.verifying_key()) else {
+            return false;
+        };
+        let Ok(signature) = frost_core::Signature::<C>::deserialize(&self.signature) else {
+            return false;
+        };
+        verifying_key.verify(message, &signature).is_ok()
+    }
+}
+
+impl WireMessage for SignerMessage {
+    const MESSAGE_TYPE: MessageType = MessageType::SignerMessage;
+}
+
+impl WireMessage for SignerResponse {
+    const MESSAGE_TYPE: MessageType = MessageType::SignerResponse;
+}
+
+impl WireMessage for CombinedSignature {
+    const MESSAGE_TYPE: MessageType = MessageType::CombinedSignature;
+}
+
+#[cfg(feature = "secp256k1-tr")]
+impl WireMessage for crate::taproot::TaprootSignature {
+    const MESSAGE_TYPE: MessageType = MessageType::TaprootSignature;
+}
+
+impl<C: Ciphersuite> WireMessage for DkgRound1Message<C> {
+    const MESSAGE_TYPE: MessageType = MessageType::DkgRound1;
+}
+
+impl<C: Ciphersuite> WireMessage for DkgRound2Message<C> {
+    const MESSAGE_TYPE: MessageType = MessageType::DkgRound2;
+}
+
+impl<C: Ciphersuite> WireMessage for ReshareMessage<C> {
+    const MESSAGE_TYPE: MessageType = MessageType::Reshare;
+}
+
+impl WireMessage for RepairDeltaMessage {
+    const MESSAGE_TYPE: MessageType = MessageType::RepairDelta;
+}
+
+impl WireMessage for RepairSigmaMessage {
+    const MESSAGE_TYPE: MessageType = MessageType::RepairSigma;
+}
+
+/// Serialize `message` inside an envelope tagged with its type, the format
+/// version and the ciphersuite `C`
+pub fn encode<C: Ciphersuite, T: WireMessage>(message: &T) -> Vec<u8> {
+    serialize(&Envelope {
+        magic: ENVELOPE_MAGIC,
+        version: ENVELOPE_VERSION,
+        message_type: T::MESSAGE_TYPE,
+        ciphersuite: C::ID.to_string(),
+        payload: serialize(message),
+    })
+}
+
+/// Decode an envelope produced by `encode`, checking every header field
+///
+/// Bytes of another protocol version fail with `Codec`; a well-formed envelope
+/// carrying another message type or ciphersuite fails with `Protocol`.
+pub fn decode<C: Ciphersuite, T: WireMessage>(bytes: &[u8]) -> Result<T, ThresholdError> {
+    match read_magic_and_version(bytes) {
+        Some((ENVELOPE_MAGIC, ENVELOPE_VERSION)) => {}
+        Some((ENVELOPE_MAGIC, version)) => {
+            return Err(ThresholdError::Codec(format!("Unsupported message format version {}", version)));
+        }
+        _ => return Err(ThresholdError::Codec("Not an enveloped message".to_string())),
+    }
+
+    let envelope: Envelope = try_deserialize(bytes)?;
+    if envelope.message_type != T::MESSAGE_TYPE {
+        return Err(ThresholdError::Protocol(format!(
+            "Expected a {:?} message, got {:?}",
+            T::MESSAGE_TYPE,
+            envelope.message_type
+        )));
+    }
+    if envelope.ciphersuite != C::ID {
+        return Err(ThresholdError::Protocol(format!(
+            "Message for ciphersuite {}, expected {}",
+            envelope.ciphersuite,
+            C::ID
+        )));
+    }
+    try_deserialize(&envelope.payload)
+}
+
+/// Leading magic and version of an envelope or keystore file, read without
+/// decoding the rest (whose layout depends on the version)
+pub(crate) fn read_magic_and_version(bytes: &[u8]) -> Option<([u8; 4], u16)> {
+    let magic = bytes.get(..4)?.try_into().ok()?;
+    let version = u16::from_le_bytes(bytes.get(4..6)?.try_into().ok()?);
+    Some((magic, version))
+}
+
+/// Encoding used on the wire and in files
+///
+/// Spelled out rather than relying on bincode's defaults: little-endian,
+/// fixed-size integers (the same bytes `bincode::serialize` produces), and
+/// trailing bytes after the value are an error.
+fn wire_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_little_endian()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
 }
 
 pub fn serialize<T: Serialize>(data: &T) -> Vec<u8> {
-    bincode::serialize(data).expect("Serialization failed")
+    wire_options().serialize(data).expect("Serialization failed")
+}
+
+/// Decode bytes that may come from a peer or any other untrusted source
+///
+/// Never panics. Fails with `ThresholdError::Codec` on malformed input, on
+/// trailing bytes, and on anything larger than `MAX_MESSAGE_SIZE` (including
+/// length prefixes announcing more than that, so no large allocation happens).
+pub fn try_deserialize<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> Result<T, ThresholdError> {
+    try_deserialize_with_limit(bytes, MAX_MESSAGE_SIZE)
+}
+
+/// `try_deserialize` with a custom size limit in bytes
+pub fn try_deserialize_with_limit<'a, T: Deserialize<'a>>(bytes: &'a [u8], limit: u64) -> Result<T, ThresholdError> {
+    if bytes.len() as u64 > limit {
+        return Err(ThresholdError::Codec(format!(
+            "Encoding of {} bytes exceeds the limit of {}",
+            bytes.len(),
+            limit
+        )));
+    }
+    wire_options()
+        .with_limit(limit)
+        .deserialize(bytes)
+        .map_err(|e| ThresholdError::Codec(format!("Deserialization failed: {}", e)))
 }
 
+/// Decode bytes this process produced itself; panics on malformed input
+///
+/// Use `try_deserialize` for anything received from outside.
 pub fn deserialize<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> T {
-    bincode::deserialize(bytes).expect("Deserialization failed")
+    try_deserialize(bytes).expect("Deserialization failed")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
 
     #[test]
     fn test_signer_message_serialization_roundtrip() {
-        let original = SignerMessage {
+        let original = SignerMessage::Round2Request {
             signer_index: 1,
-            message_hash: [42u8; 32],
-            nonce_commitment: [99u8; 32],
+            signing_package: vec![42u8; 96],
         };
 
         let serialized = serialize(&original);
@@ -49,10 +327,22 @@ mod tests {
 
     #[test]
     fn test_signer_response_serialization_roundtrip() {
-        let original = SignerResponse {
+        let original = SignerResponse::SignatureShare {
             signer_index: 2,
-            signature_share: [123u8; 32],
-            nonce_share: [45u8; 32],
+            signature_share: vec![123u8; 32],
+        };
+
+        let serialized = serialize(&original);
+        let deserialized: SignerResponse = deserialize(&serialized);
+
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn test_signer_error_response_serialization_roundtrip() {
+        let original = SignerResponse::Error {
+            signer_index: 3,
+            reason: "No signing nonces available".to_string(),
         };
 
         let serialized = serialize(&original);
@@ -64,8 +354,9 @@ mod tests {
     #[test]
     fn test_combined_signature_serialization_roundtrip() {
         let original = CombinedSignature {
-            signature: [77u8; 64],
-            public_key: [88u8; 32],
+            signature: vec![77u8; 64],
+            public_key: vec![88u8; 32],
+            randomized_public_key: Some(vec![99u8; 32]),
         };
 
         let serialized = serialize(&original);
@@ -77,10 +368,9 @@ mod tests {
     #[test]
     fn test_serialized_format_stability() {
         // Ensure serialization format is deterministic
-        let msg = SignerMessage {
+        let msg = SignerMessage::Round2Request {
             signer_index: 5,
-            message_hash: [1u8; 32],
-            nonce_commitment: [2u8; 32],
+            signing_package: vec![1u8; 32],
         };
 
         let serialized1 = serialize(&msg);
@@ -91,21 +381,110 @@ mod tests {
 
     #[test]
     fn test_different_values_produce_different_serialization() {
-        let msg1 = SignerMessage {
+        let msg1 = SignerMessage::Round1Request { signer_index: 1 };
+        let msg2 = SignerMessage::Round1Request { signer_index: 2 };
+        let msg3 = SignerMessage::Round2Request {
             signer_index: 1,
-            message_hash: [1u8; 32],
-            nonce_commitment: [1u8; 32],
-        };
-
-        let msg2 = SignerMessage {
-            signer_index: 2,
-            message_hash: [1u8; 32],
-            nonce_commitment: [1u8; 32],
+            signing_package: vec![],
         };
 
         let serialized1 = serialize(&msg1);
         let serialized2 = serialize(&msg2);
+        let serialized3 = serialize(&msg3);
 
         assert_ne!(serialized1, serialized2);
+        assert_ne!(serialized1, serialized3);
+    }
+
+    #[test]
+    fn test_encoding_matches_bincode_default() {
+        let msg = SignerMessage::Round2Request {
+            signer_index: 7,
+            signing_package: vec![9u8; 40],
+        };
+
+        assert_eq!(serialize(&msg), bincode::serialize(&msg).unwrap());
+    }
+
+    #[test]
+    fn test_try_deserialize_rejects_malformed_input() {
+        let serialized = serialize(&SignerMessage::Round1Request { signer_index: 1 });
+
+        // Truncated, unknown variant, trailing bytes
+        assert!(try_deserialize::<SignerMessage>(&serialized[..3]).is_err());
+        assert!(try_deserialize::<SignerMessage>(&[0xff, 0, 0, 0, 1, 0]).is_err());
+        let mut trailing = serialized.clone();
+        trailing.push(0);
+        assert!(matches!(
+            try_deserialize::<SignerMessage>(&trailing),
+            Err(ThresholdError::Codec(_))
+        ));
+
+        assert_eq!(
+            try_deserialize::<SignerMessage>(&serialized),
+            Ok(SignerMessage::Round1Request { signer_index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let original = CombinedSignature {
+            signature: vec![5u8; 64],
+            public_key: vec![6u8; 32],
+            randomized_public_key: None,
+        };
+
+        let encoded = encode::<E, _>(&original);
+        assert_eq!(&encoded[..4], &ENVELOPE_MAGIC);
+        assert_eq!(decode::<E, CombinedSignature>(&encoded), Ok(original));
+    }
+
+    #[test]
+    fn test_envelope_rejects_wrong_type_version_and_ciphersuite() {
+        let encoded = encode::<E, _>(&SignerMessage::Round1Request { signer_index: 1 });
+
+        // A request is not a response, even though both decode as bincode
+        assert!(matches!(
+            decode::<E, SignerResponse>(&encoded),
+            Err(ThresholdError::Protocol(_))
+        ));
+
+        let mut envelope: Envelope = deserialize(&encoded);
+        envelope.version = ENVELOPE_VERSION + 1;
+        assert_eq!(
+            decode::<E, SignerMessage>(&serialize(&envelope)),
+            Err(ThresholdError::Codec(format!(
+                "Unsupported message format version {}",
+                ENVELOPE_VERSION + 1
+            )))
+        );
+
+        let mut envelope: Envelope = deserialize(&encoded);
+        envelope.ciphersuite = "FROST-secp256k1-SHA256-v1".to_string();
+        assert!(matches!(
+            decode::<E, SignerMessage>(&serialize(&envelope)),
+            Err(ThresholdError::Protocol(_))
+        ));
+
+        // Bare bincode without an envelope
+        let bare = serialize(&SignerMessage::Round1Request { signer_index: 1 });
+        assert!(matches!(decode::<E, SignerMessage>(&bare), Err(ThresholdError::Codec(_))));
+    }
+
+    #[test]
+    fn test_try_deserialize_enforces_size_limit() {
+        // Round2Request announcing a u64::MAX-byte signing package
+        let mut hostile = 1u32.to_le_bytes().to_vec();
+        hostile.extend_from_slice(&1u16.to_le_bytes());
+        hostile.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert!(try_deserialize::<SignerMessage>(&hostile).is_err());
+
+        let msg = SignerMessage::Round2Request {
+            signer_index: 1,
+            signing_package: vec![0u8; 100],
+        };
+        let serialized = serialize(&msg);
+        assert!(try_deserialize_with_limit::<SignerMessage>(&serialized, 64).is_err());
+        assert_eq!(try_deserialize_with_limit::<SignerMessage>(&serialized, 256), Ok(msg));
     }
 }
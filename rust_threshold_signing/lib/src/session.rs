@@ -0,0 +1,493 @@
+use frost_core::{self as frost, Ciphersuite};
+use rand::{RngCore, thread_rng};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::derivation::{ChainCode, DerivationPath, derive_public_key_package};
+use crate::error::{ThresholdError, codec, identifier};
+use crate::rerandomized::Randomizer;
+use crate::serialization::{CombinedSignature, SignerResponse, decode};
+use crate::threshold::{combine_serialized_shares, unexpected_response};
+
+/// How long `sign_with_transports` lets a signing session run
+pub const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Random identifier of one signing session
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SessionId(pub [u8; 16]);
+
+impl SessionId {
+    pub fn random() -> Self {
+        let mut bytes = [0u8; 16];
+        thread_rng().fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// Where a signing session currently is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionState {
+    /// Waiting for nonce commitments from the participants
+    CollectingCommitments,
+    /// Signing package fixed, waiting for signature shares
+    CollectingShares,
+    /// Signature aggregated
+    Complete(CombinedSignature),
+    /// A participant refused or misbehaved; the session cannot complete
+    Failed(ThresholdError),
+    /// The deadline passed before these participants answered
+    TimedOut { stragglers: Vec<u16> },
+}
+
+/// One signing of one message by a fixed set of participants
+///
+/// Holds everything the coordinator knows about the signing: the message, the
+/// participants, the commitments and shares collected so far and the deadline.
+/// It is driven incrementally, one response at a time, so a coordinator can keep
+/// any number of sessions open and feed each response to the session it belongs
+/// to. Once every commitment is in, the signing package is fixed and never
+/// rebuilt, so each signer's nonce is used for exactly one package.
+///
+/// Errors caused by a response that does not belong here (wrong sender, wrong
+/// phase, duplicate) leave the session untouched; a participant refusing or
+/// sending an invalid share moves it to `Failed`.
+pub struct SigningSession<C: Ciphersuite> {
+    id: SessionId,
+    message: Vec<u8>,
+    participants: BTreeSet<u16>,
+    pubkey_package: frost::keys::PublicKeyPackage<C>,
+    deadline: Instant,
+    commitments: BTreeMap<u16, frost::round1::SigningCommitments<C>>,
+    signing_package: Option<Vec<u8>>,
+    randomizer: Option<Randomizer<C>>,
+    derivation: Option<(ChainCode, DerivationPath)>,
+    shares: BTreeMap<u16, Vec<u8>>,
+    state: SessionState,
+}
+
+impl<C: Ciphersuite> SigningSession<C> {
+    /// Start a session signing `message` with exactly `participants`
+    ///
+    /// Every participant must have a verifying share in `pubkey_package`, and there
+    /// must be at least `threshold` of them.
+    pub fn new(
+        threshold: u16,
+        pubkey_package: frost::keys::PublicKeyPackage<C>,
+        message: &[u8],
+        participants: &[u16],
+        timeout: Duration,
+    ) -> Result<Self, ThresholdError> {
+        let unique: BTreeSet<u16> = participants.iter().copied().collect();
+        if unique.len() != participants.len() {
+            return Err(ThresholdError::InvalidParameters(
+                "Signing participants contain duplicates".to_string(),
+            ));
+        }
+        if unique.len() < threshold as usize {
+            return Err(ThresholdError::InsufficientSigners {
+                required: threshold as usize,
+                available: unique.len(),
+            });
+        }
+        for &idx in &unique {
+            if !pubkey_package.verifying_shares().contains_key(&identifier(idx)?) {
+                return Err(ThresholdError::UnknownSigner(idx));
+            }
+        }
+
+        Ok(Self {
+            id: SessionId::random(),
+            message: message.to_vec(),
+            participants: unique,
+            pubkey_package,
+            deadline: Instant::now() + timeout,
+            commitments: BTreeMap::new(),
+            signing_package: None,
+            randomizer: None,
+            derivation: None,
+            shares: BTreeMap::new(),
+            state: SessionState::CollectingCommitments,
+        })
+    }
+
+    pub fn id(&self) -> SessionId {
+        self.id
+    }
+
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    pub fn participants(&self) -> Vec<u16> {
+        self.participants.iter().copied().collect()
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub fn state(&self) -> &SessionState {
+        &self.state
+    }
+
+    /// Participants the session is still waiting for in its current phase
+    pub fn pending(&self) -> Vec<u16> {
+        self.participants
+            .iter()
+            .copied()
+            .filter(|idx| match self.state {
+                SessionState::CollectingCommitments => !self.commitments.contains_key(idx),
+                SessionState::CollectingShares => !self.shares.contains_key(idx),
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Serialized `frost::SigningPackage` to send to every participant, once all
+    /// commitments are in
+    pub fn signing_package(&self) -> Option<&[u8]> {
+        self.signing_package.as_deref()
+    }
+
+    /// Randomizer the signing package is signed under, if the session is rerandomized
+    pub fn randomizer(&self) -> Option<&Randomizer<C>> {
+        self.randomizer.as_ref()
+    }
+
+    /// Chain code and path of the child key the session signs for, if any
+    pub fn derivation(&self) -> Option<(&ChainCode, &DerivationPath)> {
+        self.derivation.as_ref().map(|(chain_code, path)| (chain_code, path))
+    }
+
+    /// Sign under the group key rerandomized by `randomizer` (see `rerandomized`)
+    ///
+    /// Only possible between the signing package being fixed and the first share
+    /// arriving, and only once: every participant must sign under the same key.
+    pub fn set_randomizer(&mut self, randomizer: Randomizer<C>) -> Result<(), ThresholdError> {
+        self.check_key_unfixed()?;
+        self.randomizer = Some(randomizer);
+        Ok(())
+    }
+
+    /// Sign for the child key at `path` under `chain_code` (see `derivation`)
+    ///
+    /// Same restrictions as `set_randomizer`; a session is either derived or
+    /// rerandomized, not both. The signature carries the child key as `public_key`.
+    pub fn set_derivation(&mut self, chain_code: ChainCode, path: DerivationPath) -> Result<(), ThresholdError> {
+        self.check_key_unfixed()?;
+        self.pubkey_package = derive_public_key_package(&self.pubkey_package, &chain_code, &path)?;
+        self.derivation = Some((chain_code, path));
+        Ok(())
+    }
+
+    /// The aggregated signature, once the session is complete
+    pub fn signature(&self) -> Option<&CombinedSignature> {
+        match &self.state {
+            SessionState::Complete(signature) => Some(signature),
+            _ => None,
+        }
+    }
+
+    /// Time the session out if `now` is past its deadline
+    ///
+    /// Call periodically for sessions that might be waiting on a signer that
+    /// never answers. Returns `Timeout` naming the stragglers once expired.
+    pub fn check_deadline(&mut self, now: Instant) -> Result<(), ThresholdError> {
+        if let SessionState::TimedOut { stragglers } = &self.state {
+            return Err(ThresholdError::Timeout { stragglers: stragglers.clone() });
+        }
+        if now >= self.deadline && self.is_open() {
+            let stragglers = self.pending();
+            self.state = SessionState::TimedOut { stragglers: stragglers.clone() };
+            return Err(ThresholdError::Timeout { stragglers });
+        }
+        Ok(())
+    }
+
+    /// Record a round 1 response from `signer_index`
+    pub fn receive_commitments(&mut self, signer_index: u16, response: SignerResponse) -> Result<(), ThresholdError> {
+        match response {
+            SignerResponse::Commitments { signer_index: from, commitments } if from == signer_index => {
+                match frost::round1::SigningCommitments::<C>::deserialize(&commitments) {
+                    Ok(commitments) => self.add_commitments(signer_index, commitments),
+                    Err(_) => {
+                        self.check_phase(signer_index, SessionState::CollectingCommitments)?;
+                        Err(self.fail(ThresholdError::ShareVerification { culprits: vec![signer_index] }))
+                    }
+                }
+            }
+            other => {
+                self.check_phase(signer_index, SessionState::CollectingCommitments)?;
+                Err(self.fail(unexpected_response(signer_index, other)))
+            }
+        }
+    }
+
+    /// Record commitments `signer_index` published earlier (see `CommitmentPool`)
+    ///
+    /// The signing package is built as soon as the last participant's commitments arrive.
+    pub fn add_commitments(
+        &mut self,
+        signer_index: u16,
+        commitments: frost::round1::SigningCommitments<C>,
+    ) -> Result<(), ThresholdError> {
+        self.check_phase(signer_index, SessionState::CollectingCommitments)?;
+        if self.commitments.contains_key(&signer_index) {
+            return Err(ThresholdError::Protocol(format!(
+                "Duplicate commitments from signer {}",
+                signer_index
+            )));
+        }
+        self.commitments.insert(signer_index, commitments);
+
+        if self.commitments.len() == self.participants.len() {
+            let mut signing_commitments = BTreeMap::new();
+            for (&idx, commitment) in &self.commitments {
+                signing_commitments.insert(identifier(idx)?, *commitment);
+            }
+            let signing_package = frost::SigningPackage::<C>::new(signing_commitments, &self.message)
+                .serialize()
+                .map_err(codec("Failed to serialize signing package"))?;
+            self.signing_package = Some(signing_package);
+            self.state = SessionState::CollectingShares;
+        }
+        Ok(())
+    }
+
+    /// Record a serialized round 2 `SignerResponse` from `signer_index`
+    ///
+    /// When the last share arrives, every share is verified and aggregated: the
+    /// session ends `Complete`, or `Failed` naming the signers with invalid shares.
+    pub fn receive_share(&mut self, signer_index: u16, serialized: &[u8]) -> Result<(), ThresholdError> {
+        self.check_phase(signer_index, SessionState::CollectingShares)?;
+        if self.shares.contains_key(&signer_index) {
+            return Err(ThresholdError::Protocol(format!(
+                "Duplicate share from signer {}",
+                signer_index
+            )));
+        }
+
+        match decode::<C, _>(serialized) {
+            Ok(SignerResponse::SignatureShare { signer_index: from, .. }) if from == signer_index => {}
+            Ok(other) => return Err(self.fail(unexpected_response(signer_index, other))),
+            Err(_) => return Err(self.fail(ThresholdError::ShareVerification { culprits: vec![signer_index] })),
+        }
+        self.shares.insert(signer_index, serialized.to_vec());
+
+        if self.shares.len() == self.participants.len() {
+            let signing_package = self.signing_package.as_deref().unwrap_or_default();
+            let shares = self.shares.values().cloned().collect();
+            match combine_serialized_shares(&self.pubkey_package, self.randomizer.as_ref(), signing_package, shares) {
+                Ok(signature) => self.state = SessionState::Complete(signature),
+                Err(e) => return Err(self.fail(e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// The aggregated signature, or the reason the session did not produce one
+    pub fn finish(&self) -> Result<CombinedSignature, ThresholdError> {
+        match &self.state {
+            SessionState::Complete(signature) => Ok(signature.clone()),
+            SessionState::Failed(e) => Err(e.clone()),
+            SessionState::TimedOut { stragglers } => Err(ThresholdError::Timeout { stragglers: stragglers.clone() }),
+            _ => Err(ThresholdError::Protocol(format!(
+                "Session {} is still waiting for signers {:?}",
+                self.id,
+                self.pending()
+            ))),
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        matches!(self.state, SessionState::CollectingCommitments | SessionState::CollectingShares)
+    }
+
+    /// Refuse a response from a non-participant or for another phase, and time
+    /// the session out if its deadline passed
+    fn check_phase(&mut self, signer_index: u16, expected: SessionState) -> Result<(), ThresholdError> {
+        if !self.participants.contains(&signer_index) {
+            return Err(ThresholdError::Protocol(format!(
+                "Signer {} is not a participant of session {}",
+                signer_index, self.id
+            )));
+        }
+        self.check_deadline(Instant::now())?;
+        if self.state != expected {
+            return Err(ThresholdError::Protocol(format!(
+                "Session {} is {:?}, not {:?}",
+                self.id, self.state, expected
+            )));
+        }
+        Ok(())
+    }
+
+    /// Refuse to change the signing key once it may have been used
+    fn check_key_unfixed(&self) -> Result<(), ThresholdError> {
+        if self.state != SessionState::CollectingShares
+            || !self.shares.is_empty()
+            || self.randomizer.is_some()
+            || self.derivation.is_some()
+        {
+            return Err(ThresholdError::Protocol(format!(
+                "Session {} can only be rerandomized or derived once, before any share arrives",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+
+    fn fail(&mut self, error: ThresholdError) -> ThresholdError {
+        self.state = SessionState::Failed(error.clone());
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::serialization::{SignerMessage, encode};
+    use crate::threshold::{ThresholdSigner, generate_frost_keys};
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn signers(max_signers: u16, min_signers: u16) -> (Vec<ThresholdSigner<E>>, frost::keys::PublicKeyPackage<E>) {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(max_signers, min_signers).unwrap();
+        let signers = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        (signers, pubkey_package)
+    }
+
+    fn round2_response(signer: &mut ThresholdSigner<E>, session: &SigningSession<E>) -> Vec<u8> {
+        signer.receive_serialized_signing_request(&encode::<E, _>(&SignerMessage::Round2Request {
+            signer_index: signer.index,
+            signing_package: session.signing_package().unwrap().to_vec(),
+        }))
+    }
+
+    #[test]
+    fn test_concurrent_sessions_driven_incrementally() {
+        let (mut signers, pubkey_package) = signers(3, 2);
+
+        // Two sessions over the same signers, fed with preprocessed commitments
+        let mut first = SigningSession::new(2, pubkey_package.clone(), b"first", &[1, 2], TIMEOUT).unwrap();
+        let mut second = SigningSession::new(2, pubkey_package, b"second", &[1, 2], TIMEOUT).unwrap();
+        assert_ne!(first.id(), second.id());
+
+        for signer in signers.iter_mut().take(2) {
+            let commitments = signer.preprocess_nonces(2).unwrap();
+            first.add_commitments(signer.index, commitments[0]).unwrap();
+            assert_eq!(first.pending(), if signer.index == 1 { vec![2] } else { vec![1, 2] });
+            second.add_commitments(signer.index, commitments[1]).unwrap();
+        }
+        assert_eq!(*first.state(), SessionState::CollectingShares);
+
+        // Shares arrive interleaved and out of order
+        let share = round2_response(&mut signers[1], &second);
+        second.receive_share(2, &share).unwrap();
+        let share = round2_response(&mut signers[0], &first);
+        first.receive_share(1, &share).unwrap();
+        let share = round2_response(&mut signers[0], &second);
+        second.receive_share(1, &share).unwrap();
+        assert!(matches!(second.state(), SessionState::Complete(_)));
+        assert!(first.signature().is_none());
+        let share = round2_response(&mut signers[1], &first);
+        first.receive_share(2, &share).unwrap();
+
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        for session in [&first, &second] {
+            let combined_sig = session.finish().unwrap();
+            let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+            let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+            assert!(verifying_key.verify(session.message(), &signature).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_session_times_out_stragglers() {
+        let (mut signers, pubkey_package) = signers(3, 2);
+
+        let mut session = SigningSession::new(2, pubkey_package.clone(), b"message", &[1, 2, 3], TIMEOUT).unwrap();
+        session.add_commitments(1, signers[0].round1_generate_nonces()).unwrap();
+        session.add_commitments(3, signers[2].round1_generate_nonces()).unwrap();
+
+        assert!(session.check_deadline(Instant::now()).is_ok());
+        let expired = session.deadline() + Duration::from_millis(1);
+        assert_eq!(session.check_deadline(expired), Err(ThresholdError::Timeout { stragglers: vec![2] }));
+        assert_eq!(*session.state(), SessionState::TimedOut { stragglers: vec![2] });
+
+        // Late answers are refused
+        assert!(session.add_commitments(2, signers[1].round1_generate_nonces()).is_err());
+        assert_eq!(session.finish(), Err(ThresholdError::Timeout { stragglers: vec![2] }));
+
+        // A zero timeout expires on the first response
+        let mut session = SigningSession::new(2, pubkey_package, b"message", &[1, 2], Duration::ZERO).unwrap();
+        assert_eq!(
+            session.add_commitments(1, signers[0].round1_generate_nonces()),
+            Err(ThresholdError::Timeout { stragglers: vec![1, 2] })
+        );
+    }
+
+    #[test]
+    fn test_session_ignores_misrouted_responses_and_fails_on_bad_share() {
+        let (mut signers, pubkey_package) = signers(3, 2);
+        assert_eq!(
+            SigningSession::new(3, pubkey_package.clone(), b"m", &[1, 2], TIMEOUT).err(),
+            Some(ThresholdError::InsufficientSigners { required: 3, available: 2 })
+        );
+
+        let mut session = SigningSession::new(2, pubkey_package, b"message", &[1, 2], TIMEOUT).unwrap();
+
+        // Not a participant, duplicate, and a share before the signing package exists
+        assert!(session.add_commitments(3, signers[2].round1_generate_nonces()).is_err());
+        session.add_commitments(1, signers[0].round1_generate_nonces()).unwrap();
+        let extra = signers[0].preprocess_nonces(1).unwrap();
+        assert!(session.add_commitments(1, extra[0]).is_err());
+        assert!(session.receive_share(1, &[]).is_err());
+        assert_eq!(*session.state(), SessionState::CollectingCommitments);
+
+        session.add_commitments(2, signers[1].round1_generate_nonces()).unwrap();
+        let share = round2_response(&mut signers[0], &session);
+        session.receive_share(1, &share).unwrap();
+
+        // Signer 2 answers with signer 1's share
+        assert_eq!(
+            session.receive_share(2, &share),
+            Err(ThresholdError::Protocol(format!(
+                "Unexpected response from signer 2: {:?}",
+                decode::<E, SignerResponse>(&share).unwrap()
+            )))
+        );
+        assert!(matches!(session.state(), SessionState::Failed(_)));
+        assert!(session.finish().is_err());
+    }
+
+    #[test]
+    fn test_session_randomized_only_before_shares() {
+        let (mut signers, pubkey_package) = signers(3, 2);
+        let randomizer = Randomizer::<E>::deserialize(&[3u8; 32]).unwrap();
+
+        let mut session = SigningSession::new(2, pubkey_package, b"message", &[1, 2], TIMEOUT).unwrap();
+        assert!(session.set_randomizer(randomizer).is_err());
+        for signer in signers.iter_mut().take(2) {
+            session.add_commitments(signer.index, signer.round1_generate_nonces()).unwrap();
+        }
+        session.set_randomizer(randomizer).unwrap();
+        assert!(session.set_randomizer(randomizer).is_err());
+        assert!(session.set_derivation([0u8; 32], DerivationPath::default()).is_err());
+        assert_eq!(session.randomizer(), Some(&randomizer));
+        assert!(session.derivation().is_none());
+    }
+}
@@ -0,0 +1,216 @@
+//! BIP-340 Schnorr signatures for Bitcoin Taproot key-path spends
+//!
+//! Signing uses the `Secp256K1Sha256TR` ciphersuite like any other, so every
+//! coordinator, transport and keystore works unchanged. BIP-340 wants an x-only
+//! key with an even Y coordinate; the suite negates shares and nonces whenever
+//! the group key or nonce commitment has an odd Y, so signers and coordinators
+//! never need to normalize keys themselves.
+//!
+//! A key-path spend is signed for the output key `Q = P + t·G`, where `P` is
+//! the group key and `t` the BIP-341 tweak of an optional script tree. Tweak
+//! every signer's key package and the coordinator's public key package with the
+//! same `merkle_root` before signing. Keys from `generate_frost_keys_dkg` already
+//! carry the tweak for "no script path" (see `frost_secp256k1_tr`'s `post_dkg`),
+//! so only dealer-generated keys should be tweaked again.
+
+use frost_core::keys::{KeyPackage, PublicKeyPackage};
+use frost_secp256k1_tr::keys::Tweak;
+use frost_secp256k1_tr::Secp256K1Sha256TR;
+use k256::schnorr;
+use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
+
+use crate::error::{ThresholdError, codec};
+use crate::serialization::CombinedSignature;
+
+/// Apply the BIP-341 tweak for `merkle_root` (or no script path) to a signer's key package
+pub fn tweak_key_package(
+    key_package: KeyPackage<Secp256K1Sha256TR>,
+    merkle_root: Option<&[u8]>,
+) -> KeyPackage<Secp256K1Sha256TR> {
+    key_package.tweak(merkle_root)
+}
+
+/// Apply the BIP-341 tweak for `merkle_root` (or no script path) to a public key package
+pub fn tweak_public_key_package(
+    pubkey_package: PublicKeyPackage<Secp256K1Sha256TR>,
+    merkle_root: Option<&[u8]>,
+) -> PublicKeyPackage<Secp256K1Sha256TR> {
+    pubkey_package.tweak(merkle_root)
+}
+
+/// The group key as a 32-byte x-only key, as used in Taproot outputs
+pub fn x_only_public_key(pubkey_package: &PublicKeyPackage<Secp256K1Sha256TR>) -> Result<[u8; 32], ThresholdError> {
+    let compressed = pubkey_package
+        .verifying_key()
+        .serialize()
+        .map_err(codec("Invalid verifying key"))?;
+    x_only(&compressed)
+}
+
+/// BIP-340 signature and the x-only key it verifies under
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TaprootSignature {
+    #[serde(with = "BigArray")]
+    pub signature: [u8; 64],
+    pub public_key: [u8; 32],
+}
+
+impl TaprootSignature {
+    /// Check the signature on `message` with a standard BIP-340 verifier
+    ///
+    /// `message` goes into the BIP-340 challenge as-is, so for a Taproot spend
+    /// it is the 32-byte sighash. Malformed bytes count as an invalid signature.
+    pub fn verify(&self, message: &[u8]) -> bool {
+        let Ok(verifying_key) = schnorr::VerifyingKey::from_bytes(&self.public_key) else {
+            return false;
+        };
+        let Ok(signature) = schnorr::Signature::try_from(self.signature.as_slice()) else {
+            return false;
+        };
+        verifying_key.verify_raw(message, &signature).is_ok()
+    }
+}
+
+impl TryFrom<&CombinedSignature> for TaprootSignature {
+    type Error = ThresholdError;
+
+    /// Convert a `Secp256K1Sha256TR` signature, dropping the parity byte of the
+    /// group key: the x-only key stands for its even-Y point
+    fn try_from(combined: &CombinedSignature) -> Result<Self, ThresholdError> {
+        let signature = combined
+            .signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| {
+                ThresholdError::Codec(format!("Expected a 64-byte signature, got {} bytes", combined.signature.len()))
+            })?;
+        Ok(Self {
+            signature,
+            public_key: x_only(combined.verifying_key())?,
+        })
+    }
+}
+
+/// Drop the SEC1 parity prefix of a compressed point
+fn x_only(compressed: &[u8]) -> Result<[u8; 32], ThresholdError> {
+    match compressed {
+        [0x02 | 0x03, x @ ..] => x
+            .try_into()
+            .map_err(|_| ThresholdError::Codec(format!("Expected a 33-byte public key, got {} bytes", compressed.len()))),
+        _ => Err(ThresholdError::Codec("Public key is not a compressed secp256k1 point".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_core::SigningKey;
+    use frost_core::keys::{IdentifierList, split};
+    use frost_secp256k1_tr::keys::EvenY;
+    use rand::thread_rng;
+    use crate::serialization::{decode, encode};
+    use crate::threshold::{ThresholdCoordinator, ThresholdSigner, generate_frost_keys, generate_frost_keys_dkg};
+
+    type Tr = Secp256K1Sha256TR;
+
+    fn sign(
+        key_packages: Vec<KeyPackage<Tr>>,
+        pubkey_package: PublicKeyPackage<Tr>,
+        message: &[u8],
+    ) -> TaprootSignature {
+        let signers = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        let mut coordinator = ThresholdCoordinator::new(2, signers, pubkey_package);
+        let combined = coordinator.perform_threshold_signing(message, vec![1, 3]).unwrap();
+        assert!(combined.verify::<Tr>(message));
+        TaprootSignature::try_from(&combined).unwrap()
+    }
+
+    /// 2-of-3 group dealt from `secret`
+    fn group_from_secret(secret: SigningKey<Tr>) -> (Vec<KeyPackage<Tr>>, PublicKeyPackage<Tr>) {
+        let (shares, pubkey_package) = split(&secret, 3, 2, IdentifierList::Default, &mut thread_rng()).unwrap();
+        let key_packages = shares.into_values().map(|share| KeyPackage::try_from(share).unwrap()).collect();
+        (key_packages, pubkey_package)
+    }
+
+    #[test]
+    fn test_untweaked_signature_verifies_as_bip340() {
+        let sighash = [7u8; 32];
+        // A group key and its negation: exactly one of them has an odd Y
+        let secret = SigningKey::<Tr>::new(&mut thread_rng());
+        let negated = SigningKey::<Tr>::from_scalar(-secret.to_scalar()).unwrap();
+
+        let mut parities = Vec::new();
+        for secret in [secret, negated] {
+            let (key_packages, pubkey_package) = group_from_secret(secret);
+            let x_only_key = x_only_public_key(&pubkey_package).unwrap();
+            parities.push(pubkey_package.has_even_y());
+
+            let signature = sign(key_packages, pubkey_package, &sighash);
+            assert_eq!(signature.public_key, x_only_key);
+            assert!(signature.verify(&sighash));
+            assert!(!signature.verify(&[8u8; 32]));
+        }
+        assert_ne!(parities[0], parities[1], "Both parities of the group key must be signed for");
+    }
+
+    #[test]
+    fn test_tweaked_signature_verifies_under_output_key() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<Tr>(3, 2).unwrap();
+        let internal_key = x_only_public_key(&pubkey_package).unwrap();
+        let merkle_root = [0x42u8; 32];
+
+        let tweaked: Vec<_> = key_packages
+            .into_iter()
+            .map(|kp| tweak_key_package(kp, Some(&merkle_root)))
+            .collect();
+        let tweaked_pubkey = tweak_public_key_package(pubkey_package, Some(&merkle_root));
+        let output_key = x_only_public_key(&tweaked_pubkey).unwrap();
+        assert_ne!(output_key, internal_key);
+
+        let sighash = [9u8; 32];
+        let signature = sign(tweaked, tweaked_pubkey, &sighash);
+        assert_eq!(signature.public_key, output_key);
+        assert!(signature.verify(&sighash));
+
+        // The same signature does not verify under the internal key
+        let wrong_key = TaprootSignature {
+            public_key: internal_key,
+            ..signature
+        };
+        assert!(!wrong_key.verify(&sighash));
+    }
+
+    #[test]
+    fn test_dkg_keys_sign_for_key_path_output() {
+        let (key_packages, pubkey_package) = generate_frost_keys_dkg::<Tr>(3, 2).unwrap();
+        let sighash = [1u8; 32];
+        let signature = sign(key_packages, pubkey_package, &sighash);
+        assert!(signature.verify(&sighash));
+
+        // Taproot signatures travel in the same envelope as every other message
+        let bytes = encode::<Tr, _>(&signature);
+        assert_eq!(decode::<Tr, TaprootSignature>(&bytes).unwrap(), signature);
+    }
+
+    #[test]
+    fn test_conversion_rejects_other_encodings() {
+        let combined = CombinedSignature {
+            signature: vec![0u8; 64],
+            public_key: vec![0u8; 32],
+            randomized_public_key: None,
+        };
+        assert!(matches!(TaprootSignature::try_from(&combined), Err(ThresholdError::Codec(_))));
+
+        let combined = CombinedSignature {
+            signature: vec![0u8; 65],
+            public_key: [vec![0x02], vec![1u8; 32]].concat(),
+            randomized_public_key: None,
+        };
+        assert!(matches!(TaprootSignature::try_from(&combined), Err(ThresholdError::Codec(_))));
+    }
+}
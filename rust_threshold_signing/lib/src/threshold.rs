@@ -1,30 +1,118 @@
-use frost_ed25519 as frost;
-use std::collections::BTreeMap;
+use frost_core::{self as frost, Ciphersuite};
+use std::collections::{BTreeMap, BTreeSet};
 use rand::thread_rng;
-use sha2::Digest;
 
-use crate::serialization::{SignerMessage, SignerResponse, CombinedSignature, serialize, deserialize};
+use crate::derivation::{ChainCode, DerivationPath, group_chain_code, derive_key_package};
+use crate::dkg::DkgParticipant;
+use crate::error::{ThresholdError, codec, identifier};
+use crate::reshare::{ReshareRecipient, deal_reshare};
+use crate::repair::{repair_step1, repair_step2, repair_step3};
+use crate::rerandomized::{RandomizedCiphersuite, Randomizer, generate_randomizer, randomize_key_package, randomize_public_key_package};
+use crate::serialization::{SignerMessage, SignerResponse, CombinedSignature, decode, encode};
+use crate::session::{DEFAULT_SESSION_TIMEOUT, SigningSession};
+use crate::transport::{InMemoryTransport, SignerTransport};
+
+/// Most pregenerated nonces a signer keeps at once
+pub const MAX_PREPROCESSED_NONCES: usize = 1024;
+
+/// Most messages signed per batch round trip; longer batches are split
+pub const MAX_BATCH_SIZE: usize = 256;
 
 // Store FROST signing packages for each signer during the signing process
-pub struct ThresholdSigner {
+pub struct ThresholdSigner<C: Ciphersuite> {
     pub index: u16,
-    pub key_package: frost::keys::KeyPackage,
-    pub signing_nonces: Option<frost::round1::SigningNonces>,
-    pub signing_commitments: Option<frost::round1::SigningCommitments>,
+    pub key_package: frost::keys::KeyPackage<C>,
+    pub signing_nonces: Option<frost::round1::SigningNonces<C>>,
+    pub signing_commitments: Option<frost::round1::SigningCommitments<C>>,
+    /// Pregenerated nonces, keyed by their serialized published commitments
+    preprocessed_nonces: BTreeMap<Vec<u8>, frost::round1::SigningNonces<C>>,
+    /// Nonces of the current batch (see `batch_round1_generate_nonces`), keyed
+    /// like `preprocessed_nonces`
+    batch_nonces: BTreeMap<Vec<u8>, frost::round1::SigningNonces<C>>,
 }
 
-impl ThresholdSigner {
-    pub fn new(index: u16, key_package: frost::keys::KeyPackage) -> Self {
+impl<C: Ciphersuite> ThresholdSigner<C> {
+    pub fn new(index: u16, key_package: frost::keys::KeyPackage<C>) -> Self {
         Self {
             index,
             key_package,
             signing_nonces: None,
             signing_commitments: None,
+            preprocessed_nonces: BTreeMap::new(),
+            batch_nonces: BTreeMap::new(),
+        }
+    }
+
+    /// Preprocessing: Pregenerate `count` nonces and return their commitments
+    ///
+    /// The commitments can be published to the coordinator ahead of time. A signing
+    /// package that contains one of them is signed with the matching nonce, which is
+    /// removed from the pool so it is never used twice.
+    pub fn preprocess_nonces(&mut self, count: usize) -> Result<Vec<frost::round1::SigningCommitments<C>>, ThresholdError> {
+        if self.preprocessed_nonces.len() + count > MAX_PREPROCESSED_NONCES {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "Cannot hold more than {} preprocessed nonces ({} held)",
+                MAX_PREPROCESSED_NONCES,
+                self.preprocessed_nonces.len()
+            )));
+        }
+
+        commit_into(&self.key_package, count, &mut self.preprocessed_nonces)
+    }
+
+    /// Number of pregenerated nonces not used yet
+    pub fn preprocessed_nonce_count(&self) -> usize {
+        self.preprocessed_nonces.len()
+    }
+
+    /// Drop the pregenerated nonces behind `commitments` without signing
+    ///
+    /// For commitments a coordinator took from its pool but could not use.
+    /// Returns how many of them were still held; the rest were already used or
+    /// never generated here (e.g. before a restart).
+    pub fn discard_preprocessed_nonces(
+        &mut self,
+        commitments: &[frost::round1::SigningCommitments<C>],
+    ) -> Result<usize, ThresholdError> {
+        let mut discarded = 0;
+        for commitment in commitments {
+            let key = commitment
+                .serialize()
+                .map_err(codec("Failed to serialize commitments"))?;
+            if self.preprocessed_nonces.remove(&key).is_some() {
+                discarded += 1;
+            }
+        }
+        Ok(discarded)
+    }
+
+    /// Batch round 1: Generate one nonce per message of a batch and return their commitments
+    ///
+    /// Batch nonces are kept apart from the preprocessed pool and replace those of
+    /// any earlier batch. The batch round 2 request drops whatever it leaves unused,
+    /// so a failed batch holds at most `MAX_BATCH_SIZE` nonces until the next one.
+    pub fn batch_round1_generate_nonces(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<frost::round1::SigningCommitments<C>>, ThresholdError> {
+        if count > MAX_BATCH_SIZE {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "Batch of {} nonces exceeds the limit of {}",
+                count, MAX_BATCH_SIZE
+            )));
         }
+
+        self.batch_nonces.clear();
+        commit_into(&self.key_package, count, &mut self.batch_nonces)
+    }
+
+    /// Number of nonces held for the current batch
+    pub fn batch_nonce_count(&self) -> usize {
+        self.batch_nonces.len()
     }
 
     /// Round 1: Generate nonce commitments for signing
-    pub fn round1_generate_nonces(&mut self) -> frost::round1::SigningCommitments {
+    pub fn round1_generate_nonces(&mut self) -> frost::round1::SigningCommitments<C> {
         let mut rng = thread_rng();
         let (nonces, commitments) = frost::round1::commit(
             self.key_package.signing_share(),
@@ -32,54 +120,305 @@ impl ThresholdSigner {
         );
 
         self.signing_nonces = Some(nonces);
-        self.signing_commitments = Some(commitments.clone());
+        self.signing_commitments = Some(commitments);
 
         commitments
     }
 
     /// Round 2: Generate signature share
+    ///
+    /// Signs with the nonces behind this signer's commitment in `signing_package`:
+    /// the ones from the last `round1_generate_nonces`, a batch set, or a preprocessed set. Those
+    /// nonces are consumed, so a second package reusing the same commitment is refused;
+    /// two shares under one nonce would reveal the signing share. A package for another
+    /// message, or one without this signer's own commitment, is refused before any nonce
+    /// is touched.
     pub fn round2_sign(
-        &self,
-        _message: &[u8],
-        signing_package: &frost::SigningPackage,
-    ) -> Result<frost::round2::SignatureShare, String> {
-        let nonces = self.signing_nonces.as_ref()
-            .ok_or("No signing nonces available")?;
+        &mut self,
+        message: &[u8],
+        signing_package: &frost::SigningPackage<C>,
+    ) -> Result<frost::round2::SignatureShare<C>, ThresholdError> {
+        let nonces = self.take_nonces(message, signing_package)?;
+        frost::round2::sign(signing_package, &nonces, &self.key_package)
+            .map_err(|e| ThresholdError::Protocol(format!("Signing failed: {:?}", e)))
+    }
+
+    /// Round 2 of rerandomized signing: like `round2_sign`, but the share is for
+    /// the group key rerandomized by `randomizer` (see `rerandomized`)
+    pub fn round2_sign_randomized(
+        &mut self,
+        message: &[u8],
+        signing_package: &frost::SigningPackage<C>,
+        randomizer: &Randomizer<C>,
+    ) -> Result<frost::round2::SignatureShare<C>, ThresholdError> {
+        let key_package = randomize_key_package(&self.key_package, randomizer)?;
+        let nonces = self.take_nonces(message, signing_package)?;
+        frost::round2::sign(signing_package, &nonces, &key_package)
+            .map_err(|e| ThresholdError::Protocol(format!("Signing failed: {:?}", e)))
+    }
+
+    /// Round 2 at a derivation path: like `round2_sign`, but the share is for the
+    /// child key at `path` under `chain_code` (see `derivation`)
+    pub fn round2_sign_derived(
+        &mut self,
+        message: &[u8],
+        signing_package: &frost::SigningPackage<C>,
+        chain_code: &ChainCode,
+        path: &DerivationPath,
+    ) -> Result<frost::round2::SignatureShare<C>, ThresholdError> {
+        let key_package = derive_key_package(&self.key_package, chain_code, path)?;
+        let nonces = self.take_nonces(message, signing_package)?;
+        frost::round2::sign(signing_package, &nonces, &key_package)
+            .map_err(|e| ThresholdError::Protocol(format!("Signing failed: {:?}", e)))
+    }
 
-        frost::round2::sign(signing_package, nonces, &self.key_package)
-            .map_err(|e| format!("Signing failed: {:?}", e))
+    /// Remove and return the nonces behind this signer's commitment in `signing_package`
+    fn take_nonces(
+        &mut self,
+        message: &[u8],
+        signing_package: &frost::SigningPackage<C>,
+    ) -> Result<frost::round1::SigningNonces<C>, ThresholdError> {
+        if signing_package.message() != message {
+            return Err(ThresholdError::Protocol("Signing package is for a different message".to_string()));
+        }
+        let commitments = signing_package
+            .signing_commitments()
+            .get(self.key_package.identifier())
+            .ok_or_else(|| ThresholdError::Protocol(format!(
+                "Signing package has no commitment from signer {}",
+                self.index
+            )))?;
+
+        let nonces = if self.signing_commitments.as_ref() == Some(commitments) {
+            self.signing_commitments = None;
+            self.signing_nonces.take()
+        } else {
+            let key = commitments
+                .serialize()
+                .map_err(codec("Failed to serialize commitments"))?;
+            self.batch_nonces
+                .remove(&key)
+                .or_else(|| self.preprocessed_nonces.remove(&key))
+        }.ok_or(ThresholdError::UnknownNonces { signer_index: self.index })?;
+        Ok(nonces)
     }
 
-    /// Receive a serialized signing request and return a serialized response
+    /// Receive a serialized `SignerMessage` and return a serialized `SignerResponse`
+    ///
+    /// This is the whole protocol surface of a signer: the in-memory coordinator and
+    /// out-of-process signers both exchange exactly these bytes. Malformed or
+    /// oversized requests are answered with `SignerResponse::Error`.
     pub fn receive_serialized_signing_request(&mut self, serialized_msg: &[u8]) -> Vec<u8> {
-        let msg: SignerMessage = deserialize(serialized_msg);
+        let response = decode::<C, _>(serialized_msg)
+            .and_then(|msg| self.handle_request(msg))
+            .unwrap_or_else(|e| self.error_response(e));
+
+        encode::<C, _>(&response)
+    }
 
-        // Generate nonce commitments
-        let _commitments = self.round1_generate_nonces();
+    /// Response reporting `error`; missing nonces get their own variant so the
+    /// coordinator can tell stale commitments from a refusal
+    fn error_response(&self, error: ThresholdError) -> SignerResponse {
+        match error {
+            ThresholdError::UnknownNonces { .. } => SignerResponse::UnknownNonces { signer_index: self.index },
+            e => SignerResponse::Error {
+                signer_index: self.index,
+                reason: e.to_string(),
+            },
+        }
+    }
 
-        // For demo purposes, we'll serialize the commitments as signature share
-        // In a real implementation, this would be handled by the coordinator
-        let response = SignerResponse {
-            signer_index: self.index as u8,
-            signature_share: msg.message_hash, // Placeholder
-            nonce_share: msg.nonce_commitment,  // Placeholder
-        };
+    fn handle_request(&mut self, msg: SignerMessage) -> Result<SignerResponse, ThresholdError> {
+        match msg {
+            SignerMessage::Round1Request { signer_index } => {
+                self.check_addressed_to_me(signer_index)?;
+                let commitments = self.round1_generate_nonces()
+                    .serialize()
+                    .map_err(codec("Failed to serialize commitments"))?;
+
+                Ok(SignerResponse::Commitments {
+                    signer_index: self.index,
+                    commitments,
+                })
+            }
+            SignerMessage::Round2Request { signer_index, signing_package } => {
+                self.check_addressed_to_me(signer_index)?;
+                self.sign_serialized_package(&signing_package)
+            }
+            SignerMessage::RandomizedRound2Request { signer_index, signing_package, randomizer } => {
+                self.check_addressed_to_me(signer_index)?;
+                let signing_package = frost::SigningPackage::<C>::deserialize(&signing_package)
+                    .map_err(codec("Invalid signing package"))?;
+                let randomizer = Randomizer::<C>::deserialize(&randomizer).map_err(codec("Invalid randomizer"))?;
+                let signature_share = self
+                    .round2_sign_randomized(signing_package.message(), &signing_package, &randomizer)?
+                    .serialize();
+
+                Ok(SignerResponse::SignatureShare {
+                    signer_index: self.index,
+                    signature_share,
+                })
+            }
+            SignerMessage::DerivedRound2Request { signer_index, signing_package, chain_code, path } => {
+                self.check_addressed_to_me(signer_index)?;
+                let signing_package = frost::SigningPackage::<C>::deserialize(&signing_package)
+                    .map_err(codec("Invalid signing package"))?;
+                let path = DerivationPath::new(path)?;
+                let signature_share = self
+                    .round2_sign_derived(signing_package.message(), &signing_package, &chain_code, &path)?
+                    .serialize();
+
+                Ok(SignerResponse::SignatureShare {
+                    signer_index: self.index,
+                    signature_share,
+                })
+            }
+            SignerMessage::PreprocessRequest { signer_index, count } => {
+                self.check_addressed_to_me(signer_index)?;
+                let commitments = self.preprocess_nonces(count as usize)?
+                    .iter()
+                    .map(|c| c.serialize())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(codec("Failed to serialize commitments"))?;
+
+                Ok(SignerResponse::PreprocessedCommitments {
+                    signer_index: self.index,
+                    commitments,
+                })
+            }
+            SignerMessage::DiscardNoncesRequest { signer_index, commitments } => {
+                self.check_addressed_to_me(signer_index)?;
+                let commitments = commitments
+                    .iter()
+                    .map(|bytes| frost::round1::SigningCommitments::<C>::deserialize(bytes))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(codec("Invalid commitments"))?;
+                let discarded = self.discard_preprocessed_nonces(&commitments)?;
+
+                Ok(SignerResponse::NoncesDiscarded {
+                    signer_index: self.index,
+                    discarded: discarded as u16,
+                })
+            }
+            SignerMessage::BatchRound1Request { signer_index, count } => {
+                self.check_addressed_to_me(signer_index)?;
+                let commitments = self.batch_round1_generate_nonces(count as usize)?
+                    .iter()
+                    .map(|c| c.serialize())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(codec("Failed to serialize commitments"))?;
+
+                Ok(SignerResponse::PreprocessedCommitments {
+                    signer_index: self.index,
+                    commitments,
+                })
+            }
+            SignerMessage::BatchRound2Request { signer_index, signing_packages } => {
+                self.check_addressed_to_me(signer_index)?;
+                let responses = self.sign_serialized_packages(&signing_packages);
+                // The batch is over: nonces no package used are never needed again
+                self.batch_nonces.clear();
+
+                Ok(SignerResponse::BatchSignatureShares {
+                    signer_index: self.index,
+                    responses: responses?,
+                })
+            }
+        }
+    }
+
+    /// Sign each package of a batch; each one succeeds or fails on its own
+    fn sign_serialized_packages(&mut self, signing_packages: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, ThresholdError> {
+        if signing_packages.len() > MAX_BATCH_SIZE {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "Batch of {} signing packages exceeds the limit of {}",
+                signing_packages.len(),
+                MAX_BATCH_SIZE
+            )));
+        }
+
+        let responses = signing_packages
+            .iter()
+            .map(|package| {
+                let response = self
+                    .sign_serialized_package(package)
+                    .unwrap_or_else(|e| self.error_response(e));
+                encode::<C, _>(&response)
+            })
+            .collect();
+        Ok(responses)
+    }
+
+    fn sign_serialized_package(&mut self, signing_package: &[u8]) -> Result<SignerResponse, ThresholdError> {
+        let signing_package = frost::SigningPackage::<C>::deserialize(signing_package)
+            .map_err(codec("Invalid signing package"))?;
+        let signature_share = self.round2_sign(signing_package.message(), &signing_package)?
+            .serialize();
 
-        serialize(&response)
+        Ok(SignerResponse::SignatureShare {
+            signer_index: self.index,
+            signature_share,
+        })
+    }
+
+    fn check_addressed_to_me(&self, signer_index: u16) -> Result<(), ThresholdError> {
+        if signer_index != self.index {
+            return Err(ThresholdError::Protocol(format!(
+                "Request for signer {} delivered to signer {}",
+                signer_index, self.index
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Generate `count` nonces into `store`, keyed by their serialized commitments,
+/// and return the commitments
+fn commit_into<C: Ciphersuite>(
+    key_package: &frost::keys::KeyPackage<C>,
+    count: usize,
+    store: &mut BTreeMap<Vec<u8>, frost::round1::SigningNonces<C>>,
+) -> Result<Vec<frost::round1::SigningCommitments<C>>, ThresholdError> {
+    let mut rng = thread_rng();
+    let mut published = Vec::with_capacity(count);
+    while published.len() < count {
+        let (nonces, commitments) = frost::round1::commit(key_package.signing_share(), &mut rng);
+        let key = commitments
+            .serialize()
+            .map_err(codec("Failed to serialize commitments"))?;
+        store.insert(key, nonces);
+        published.push(commitments);
     }
+
+    Ok(published)
+}
+
+/// Outcome of `perform_robust_threshold_signing`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobustSigningReport {
+    /// Signers whose shares failed verification and were excluded
+    pub excluded_signers: Vec<u16>,
+    /// Signers whose shares make up the final signature
+    pub signers_used: Vec<u16>,
+    /// Number of signing attempts (1 when every signer was honest)
+    pub attempts: usize,
 }
 
-pub struct ThresholdCoordinator {
+/// Coordinator that owns every signer in-process
+///
+/// Convenient for demos and tests, but whoever runs it holds every `KeyPackage`.
+/// Deployments should use `PublicCoordinator`, which only holds public data.
+pub struct ThresholdCoordinator<C: Ciphersuite> {
     pub threshold: u16,
-    pub signers: Vec<ThresholdSigner>,
-    pub pubkey_package: frost::keys::PublicKeyPackage,
+    pub signers: Vec<ThresholdSigner<C>>,
+    pub pubkey_package: frost::keys::PublicKeyPackage<C>,
 }
 
-impl ThresholdCoordinator {
+impl<C: Ciphersuite> ThresholdCoordinator<C> {
     pub fn new(
         threshold: u16,
-        signers: Vec<ThresholdSigner>,
-        pubkey_package: frost::keys::PublicKeyPackage,
+        signers: Vec<ThresholdSigner<C>>,
+        pubkey_package: frost::keys::PublicKeyPackage<C>,
     ) -> Self {
         Self {
             threshold,
@@ -88,265 +427,722 @@ impl ThresholdCoordinator {
         }
     }
 
-    /// Send signing request to a specific signer
-    pub fn send_to_signer(&mut self, signer_index: usize, message: &[u8]) -> Vec<u8> {
-        let msg_hash = sha2::Sha256::digest(message);
-        let mut msg_hash_bytes = [0u8; 32];
-        msg_hash_bytes.copy_from_slice(&msg_hash);
+    /// Perform complete threshold signing process
+    ///
+    /// Drives this coordinator's own signers through `InMemoryTransport`, so every
+    /// exchange goes through the same serialized protocol as a remote signer.
+    pub fn perform_threshold_signing(
+        &mut self,
+        message: &[u8],
+        signer_indices: Vec<u16>,
+    ) -> Result<CombinedSignature, ThresholdError> {
+        let (threshold, pubkey_package) = (self.threshold, self.pubkey_package.clone());
+        self.with_transports(&signer_indices, |endpoints| {
+            sign_with_transports(threshold, &pubkey_package, message, endpoints)
+        })
+    }
 
-        let signer_msg = SignerMessage {
-            signer_index: signer_index as u8,
-            message_hash: msg_hash_bytes,
-            nonce_commitment: [0u8; 32], // Placeholder
-        };
+    /// Perform threshold signing under a rerandomized group key (see `rerandomized`)
+    ///
+    /// Uses `randomizer` if given, otherwise a fresh one bound to the signing
+    /// package. The result carries the one-time key in `randomized_public_key`.
+    pub fn perform_randomized_threshold_signing(
+        &mut self,
+        message: &[u8],
+        signer_indices: Vec<u16>,
+        randomizer: Option<Randomizer<C>>,
+    ) -> Result<CombinedSignature, ThresholdError>
+    where
+        C: RandomizedCiphersuite,
+    {
+        let (threshold, pubkey_package) = (self.threshold, self.pubkey_package.clone());
+        self.with_transports(&signer_indices, |endpoints| {
+            let mut session = collect_commitments(threshold, &pubkey_package, message, endpoints)?;
+            randomize_session(&mut session, randomizer)?;
+            run_round2(&mut session, endpoints)
+        })
+    }
 
-        let serialized_request = serialize(&signer_msg);
+    /// Perform threshold signing for the child key at `path` (see `derivation`)
+    ///
+    /// Children are derived with the group's default chain code
+    /// (`group_chain_code`). The result carries the child key as `public_key`.
+    pub fn perform_threshold_signing_at(
+        &mut self,
+        path: &DerivationPath,
+        message: &[u8],
+        signer_indices: Vec<u16>,
+    ) -> Result<CombinedSignature, ThresholdError> {
+        let (threshold, pubkey_package) = (self.threshold, self.pubkey_package.clone());
+        let chain_code = group_chain_code(&pubkey_package)?;
+        self.with_transports(&signer_indices, |endpoints| {
+            let mut session = collect_commitments(threshold, &pubkey_package, message, endpoints)?;
+            session.set_derivation(chain_code, path.clone())?;
+            run_round2(&mut session, endpoints)
+        })
+    }
 
-        // Simulate network call - in reality this would go over HTTP/gRPC
-        self.signers[signer_index].receive_serialized_signing_request(&serialized_request)
+    /// Sign every message in `messages` with the same signer set
+    ///
+    /// See `sign_batch_with_transports`. Returns one result per message, in order;
+    /// the outer error is reserved for failures that affect the whole batch.
+    pub fn perform_batch_signing<M: AsRef<[u8]>>(
+        &mut self,
+        messages: &[M],
+        signer_indices: Vec<u16>,
+    ) -> Result<Vec<Result<CombinedSignature, ThresholdError>>, ThresholdError> {
+        let (threshold, pubkey_package) = (self.threshold, self.pubkey_package.clone());
+        self.with_transports(&signer_indices, |endpoints| {
+            sign_batch_with_transports(threshold, &pubkey_package, messages, endpoints)
+        })
     }
 
-    /// Perform complete threshold signing process
-    pub fn perform_threshold_signing(
+    /// Run `f` with an `InMemoryTransport` to each of `signer_indices`
+    fn with_transports<T>(
+        &mut self,
+        signer_indices: &[u16],
+        f: impl FnOnce(&mut [&mut dyn SignerTransport]) -> Result<T, ThresholdError>,
+    ) -> Result<T, ThresholdError> {
+        if let Some(unknown) = signer_indices
+            .iter()
+            .find(|&&idx| !self.signers.iter().any(|s| s.index == idx))
+        {
+            return Err(ThresholdError::UnknownSigner(*unknown));
+        }
+        check_distinct_signers(signer_indices)?;
+
+        let mut transports: Vec<_> = self.signers
+            .iter_mut()
+            .filter(|signer| signer_indices.contains(&signer.index))
+            .map(InMemoryTransport::new)
+            .collect();
+        let mut endpoints: Vec<&mut dyn SignerTransport> = transports
+            .iter_mut()
+            .map(|t| t as &mut dyn SignerTransport)
+            .collect();
+
+        f(&mut endpoints)
+    }
+
+    /// Perform complete threshold signing process with signers reached through
+    /// arbitrary transports (other processes, other machines)
+    pub fn perform_threshold_signing_with_transports(
+        &self,
+        message: &[u8],
+        transports: &mut [&mut dyn SignerTransport],
+    ) -> Result<CombinedSignature, ThresholdError> {
+        sign_with_transports(self.threshold, &self.pubkey_package, message, transports)
+    }
+
+    /// Perform threshold signing, tolerating misbehaving signers
+    ///
+    /// Takes an over-provisioned signer set (e.g. threshold + 2). Every attempt runs
+    /// both rounds with all remaining candidates, so every cheater in the set is
+    /// identified at once. Identified cheaters are excluded and both rounds are re-run
+    /// with the remaining signers, since the Lagrange coefficients depend on the exact
+    /// signing set. Fails once fewer than `threshold` honest candidates remain.
+    pub fn perform_robust_threshold_signing(
         &mut self,
         message: &[u8],
         signer_indices: Vec<u16>,
-    ) -> Result<CombinedSignature, String> {
-        if signer_indices.len() < self.threshold as usize {
-            return Err(format!(
-                "Not enough signers: {} < {}",
-                signer_indices.len(),
-                self.threshold
-            ));
+    ) -> Result<(CombinedSignature, RobustSigningReport), ThresholdError> {
+        let threshold = self.threshold;
+        robust_signing(threshold, signer_indices, |candidates| {
+            self.perform_threshold_signing(message, candidates)
+        })
+    }
+
+    /// Combine serialized round 2 responses into the final signature
+    ///
+    /// `signing_package` is the serialized `frost::SigningPackage` sent to the signers
+    /// and `serialized_shares` their serialized `SignerResponse::SignatureShare`s. Each
+    /// share is verified individually before aggregation, so a bad share yields
+    /// `ThresholdError::ShareVerification` naming the misbehaving signers.
+    pub fn combine_signatures(
+        &self,
+        signing_package: &[u8],
+        serialized_shares: Vec<Vec<u8>>,
+    ) -> Result<CombinedSignature, ThresholdError> {
+        combine_serialized_shares(&self.pubkey_package, None, signing_package, serialized_shares)
+    }
+}
+
+/// Refuse a signer set that names someone twice, rather than silently signing
+/// with fewer signers than asked for
+pub(crate) fn check_distinct_signers(signer_indices: &[u16]) -> Result<(), ThresholdError> {
+    let unique: BTreeSet<&u16> = signer_indices.iter().collect();
+    if unique.len() != signer_indices.len() {
+        return Err(ThresholdError::InvalidParameters(
+            "Signing participants contain duplicates".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Retry `sign` with shrinking candidate sets until it succeeds without cheaters
+/// (see `ThresholdCoordinator::perform_robust_threshold_signing`)
+pub(crate) fn robust_signing<F>(
+    threshold: u16,
+    signer_indices: Vec<u16>,
+    mut sign: F,
+) -> Result<(CombinedSignature, RobustSigningReport), ThresholdError>
+where
+    F: FnMut(Vec<u16>) -> Result<CombinedSignature, ThresholdError>,
+{
+    let mut candidates = signer_indices;
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let mut excluded_signers = Vec::new();
+    let mut attempts = 0;
+
+    while candidates.len() >= threshold as usize {
+        attempts += 1;
+        match sign(candidates.clone()) {
+            Ok(signature) => {
+                let report = RobustSigningReport {
+                    excluded_signers,
+                    signers_used: candidates,
+                    attempts,
+                };
+                return Ok((signature, report));
+            }
+            Err(ThresholdError::ShareVerification { culprits }) => {
+                candidates.retain(|idx| !culprits.contains(idx));
+                excluded_signers.extend(culprits);
+            }
+            Err(e) => return Err(e),
         }
+    }
 
-        // Round 1: Collect nonce commitments from all signers
-        let mut commitments = BTreeMap::new();
-        for &idx in &signer_indices {
-            // idx is the signer's identifier (1-based), convert to 0-based for Vec indexing
-            let signer = &mut self.signers[(idx - 1) as usize];
-            let commitment = signer.round1_generate_nonces();
-            let identifier = frost::Identifier::try_from(idx)
-                .map_err(|e| format!("Invalid identifier: {:?}", e))?;
-            commitments.insert(identifier, commitment);
+    if excluded_signers.is_empty() {
+        return Err(ThresholdError::InsufficientSigners {
+            required: threshold as usize,
+            available: candidates.len(),
+        });
+    }
+
+    // Not enough honest signers left; report everyone who misbehaved
+    excluded_signers.sort_unstable();
+    Err(ThresholdError::ShareVerification { culprits: excluded_signers })
+}
+
+/// Run both signing rounds against `transports` and aggregate the result
+pub(crate) fn sign_with_transports<C: Ciphersuite>(
+    threshold: u16,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    message: &[u8],
+    transports: &mut [&mut dyn SignerTransport],
+) -> Result<CombinedSignature, ThresholdError> {
+    let mut session = collect_commitments(threshold, pubkey_package, message, transports)?;
+    run_round2(&mut session, transports)
+}
+
+/// Round 1: start a session and collect nonce commitments from all signers
+pub(crate) fn collect_commitments<C: Ciphersuite>(
+    threshold: u16,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    message: &[u8],
+    transports: &mut [&mut dyn SignerTransport],
+) -> Result<SigningSession<C>, ThresholdError> {
+    let mut session = new_session(threshold, pubkey_package, message, transports)?;
+    for transport in transports.iter_mut() {
+        let response = transport.send_round1_request()?;
+        session.receive_commitments(transport.signer_index(), response)?;
+    }
+    Ok(session)
+}
+
+/// Start a session from commitments the signers already published (from round 1
+/// or preprocessing), ready for round 2
+pub(crate) fn session_with_commitments<C: Ciphersuite>(
+    threshold: u16,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    message: &[u8],
+    transports: &[&mut dyn SignerTransport],
+    commitments: BTreeMap<u16, frost::round1::SigningCommitments<C>>,
+) -> Result<SigningSession<C>, ThresholdError> {
+    let mut session = new_session(threshold, pubkey_package, message, transports)?;
+    for (idx, commitment) in commitments {
+        session.add_commitments(idx, commitment)?;
+    }
+    Ok(session)
+}
+
+/// Rerandomize `session` with `randomizer`, or a fresh one bound to its signing package
+pub(crate) fn randomize_session<C: RandomizedCiphersuite>(
+    session: &mut SigningSession<C>,
+    randomizer: Option<Randomizer<C>>,
+) -> Result<(), ThresholdError> {
+    let randomizer = match (randomizer, session.signing_package()) {
+        (Some(randomizer), _) => randomizer,
+        (None, Some(signing_package)) => generate_randomizer(signing_package)?,
+        (None, None) => {
+            return Err(ThresholdError::Protocol(format!(
+                "No commitments from signers {:?}",
+                session.pending()
+            )));
         }
+    };
+    session.set_randomizer(randomizer)
+}
 
-        // Create signing package
-        let signing_package = frost::SigningPackage::new(commitments, message);
+/// Sign many messages with the signers at the other end of `transports`
+///
+/// Messages are processed in chunks of `MAX_BATCH_SIZE`. For each chunk, every
+/// signer gets one batch round 1 request for one fresh nonce per message and one
+/// batch round 2 request, so a chunk costs two round trips per signer however
+/// many messages it holds. Each message has its own `SigningSession`, so a bad
+/// share or a refusal fails only that message. A transport failure or an
+/// unexpected response aborts the whole batch.
+pub(crate) fn sign_batch_with_transports<C: Ciphersuite, M: AsRef<[u8]>>(
+    threshold: u16,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    messages: &[M],
+    transports: &mut [&mut dyn SignerTransport],
+) -> Result<Vec<Result<CombinedSignature, ThresholdError>>, ThresholdError> {
+    let mut results = Vec::with_capacity(messages.len());
+    for chunk in messages.chunks(MAX_BATCH_SIZE) {
+        match sign_batch_chunk(threshold, pubkey_package, chunk, transports) {
+            Ok(chunk_results) => results.extend(chunk_results),
+            Err(e) => {
+                // An empty round 2 makes every signer still reachable drop the
+                // nonces it generated for this chunk
+                for transport in transports.iter_mut() {
+                    transport.send_signing_packages(&[]).ok();
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok(results)
+}
 
-        // Round 2: Collect signature shares
-        let mut signature_shares = BTreeMap::new();
-        for &idx in &signer_indices {
-            let identifier = frost::Identifier::try_from(idx)
-                .map_err(|e| format!("Invalid identifier: {:?}", e))?;
-            // idx is the signer's identifier (1-based), convert to 0-based for Vec indexing
-            let signer = &self.signers[(idx - 1) as usize];
-            let share = signer.round2_sign(message, &signing_package)?;
-            signature_shares.insert(identifier, share);
-        }
-
-        // Aggregate signature shares into final signature
-        let group_signature = frost::aggregate(&signing_package, &signature_shares, &self.pubkey_package)
-            .map_err(|e| format!("Aggregation failed: {:?}", e))?;
-
-        // Convert to ed25519-dalek format
-        let sig_vec = group_signature.serialize()
-            .map_err(|e| format!("Failed to serialize signature: {:?}", e))?;
-        let signature_bytes: [u8; 64] = sig_vec
-            .as_slice()
-            .try_into()
-            .expect("FROST signature should be 64 bytes");
-        let vk_vec = self.pubkey_package.verifying_key().serialize()
-            .map_err(|e| format!("Failed to serialize verifying key: {:?}", e))?;
-        let verifying_key_bytes: [u8; 32] = vk_vec
-            .as_slice()
-            .try_into()
-            .expect("Verifying key should be 32 bytes");
-
-        Ok(CombinedSignature {
-            signature: signature_bytes,
-            public_key: verifying_key_bytes,
-        })
+/// Both rounds of batch signing for at most `MAX_BATCH_SIZE` messages
+fn sign_batch_chunk<C: Ciphersuite, M: AsRef<[u8]>>(
+    threshold: u16,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    chunk: &[M],
+    transports: &mut [&mut dyn SignerTransport],
+) -> Result<Vec<Result<CombinedSignature, ThresholdError>>, ThresholdError> {
+    let mut sessions = chunk
+        .iter()
+        .map(|message| new_session(threshold, pubkey_package, message.as_ref(), transports))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Round 1: one nonce per message from every signer.
+    // Failures are recorded in the session they concern and reported by `finish()`.
+    for transport in transports.iter_mut() {
+        let idx = transport.signer_index();
+        let commitments = match transport.send_batch_round1_request(chunk.len() as u16)? {
+            SignerResponse::PreprocessedCommitments { signer_index, commitments }
+                if signer_index == idx && commitments.len() == chunk.len() => commitments,
+            other => return Err(unexpected_response(idx, other)),
+        };
+        for (session, commitments) in sessions.iter_mut().zip(commitments) {
+            let response = SignerResponse::Commitments { signer_index: idx, commitments };
+            session.receive_commitments(idx, response).ok();
+        }
+    }
+
+    // Round 2: every signing package in one request per signer. Sent even when
+    // no session is ready, since it also ends the batch on the signer.
+    let ready: Vec<usize> = (0..sessions.len())
+        .filter(|&i| sessions[i].signing_package().is_some())
+        .collect();
+    let packages: Vec<Vec<u8>> = ready
+        .iter()
+        .filter_map(|&i| sessions[i].signing_package().map(<[u8]>::to_vec))
+        .collect();
+    for transport in transports.iter_mut() {
+        let idx = transport.signer_index();
+        let shares = transport.send_signing_packages(&packages)?;
+        for (&i, share) in ready.iter().zip(shares) {
+            sessions[i].receive_share(idx, &share).ok();
+        }
+    }
+
+    Ok(sessions.iter().map(SigningSession::finish).collect())
+}
+
+/// Session signing with exactly the signers at the other end of `transports`
+fn new_session<C: Ciphersuite>(
+    threshold: u16,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    message: &[u8],
+    transports: &[&mut dyn SignerTransport],
+) -> Result<SigningSession<C>, ThresholdError> {
+    let participants: Vec<u16> = transports.iter().map(|t| t.signer_index()).collect();
+    SigningSession::new(threshold, pubkey_package.clone(), message, &participants, DEFAULT_SESSION_TIMEOUT)
+}
+
+/// Round 2: Send the session's signing package (and randomizer or derivation
+/// path, if any) to each signer and record its share
+pub(crate) fn run_round2<C: Ciphersuite>(
+    session: &mut SigningSession<C>,
+    transports: &mut [&mut dyn SignerTransport],
+) -> Result<CombinedSignature, ThresholdError> {
+    let Some(signing_package) = session.signing_package().map(<[u8]>::to_vec) else {
+        return Err(ThresholdError::Protocol(format!(
+            "No commitments from signers {:?}",
+            session.pending()
+        )));
+    };
+
+    let randomizer = session.randomizer().map(Randomizer::serialize);
+    for transport in transports.iter_mut() {
+        let response = match (&randomizer, session.derivation()) {
+            (Some(randomizer), _) => transport.send_randomized_signing_package(&signing_package, randomizer),
+            (None, Some((chain_code, path))) => {
+                transport.send_derived_signing_package(&signing_package, chain_code, path)
+            }
+            (None, None) => transport.send_signing_package(&signing_package),
+        };
+        // A reply that does not decode is a failed exchange, not proof of a bad
+        // share: only shares that fail verification make the signer a culprit
+        let response = response.map_err(|e| match e {
+            ThresholdError::Codec(reason) => ThresholdError::Transport {
+                signer_index: transport.signer_index(),
+                reason: format!("undecodable response: {}", reason),
+            },
+            e => e,
+        })?;
+        session.receive_share(transport.signer_index(), &encode::<C, _>(&response))?;
     }
 
-    /// Combine signature shares (simplified version for demonstration)
-    pub fn combine_signatures(&self, _serialized_shares: Vec<Vec<u8>>) -> CombinedSignature {
-        // This is a placeholder - real implementation uses perform_threshold_signing
-        let vk_vec = self.pubkey_package.verifying_key().serialize()
-            .expect("Failed to serialize verifying key");
-        let verifying_key_bytes: [u8; 32] = vk_vec
-            .as_slice()
-            .try_into()
-            .expect("Verifying key should be 32 bytes");
-        CombinedSignature {
-            signature: [0u8; 64],
-            public_key: verifying_key_bytes,
+    session.finish()
+}
+
+/// Verify and aggregate serialized round 2 responses (see `combine_signatures`)
+///
+/// With a `randomizer`, the shares are checked and aggregated against the
+/// rerandomized public key package.
+pub(crate) fn combine_serialized_shares<C: Ciphersuite>(
+    group_pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    randomizer: Option<&Randomizer<C>>,
+    signing_package: &[u8],
+    serialized_shares: Vec<Vec<u8>>,
+) -> Result<CombinedSignature, ThresholdError> {
+    let signing_package = frost::SigningPackage::<C>::deserialize(signing_package)
+        .map_err(codec("Invalid signing package"))?;
+    let randomized_package = randomizer.map(|r| randomize_public_key_package(group_pubkey_package, r));
+    let pubkey_package = randomized_package.as_ref().unwrap_or(group_pubkey_package);
+
+    let mut signature_shares = BTreeMap::new();
+    let mut culprits = Vec::new();
+    for serialized in &serialized_shares {
+        let response: SignerResponse = decode::<C, _>(serialized)?;
+        match response {
+            SignerResponse::SignatureShare { signer_index, signature_share } => {
+                match frost::round2::SignatureShare::<C>::deserialize(&signature_share) {
+                    Ok(share) => {
+                        if signature_shares.insert(signer_index, share).is_some() {
+                            return Err(ThresholdError::Protocol(format!(
+                                "Duplicate share from signer {}",
+                                signer_index
+                            )));
+                        }
+                    }
+                    // An undecodable share is as much misbehavior as an invalid one
+                    Err(_) => culprits.push(signer_index),
+                }
+            }
+            SignerResponse::Error { signer_index, reason } => {
+                return Err(ThresholdError::SignerRejected { signer_index, reason });
+            }
+            SignerResponse::UnknownNonces { signer_index } => {
+                return Err(ThresholdError::UnknownNonces { signer_index });
+            }
+            other => {
+                return Err(ThresholdError::Protocol(format!("Expected a signature share, got {:?}", other)));
+            }
         }
     }
+
+    // Verify each share individually so misbehaving signers can be identified
+    culprits.extend(find_invalid_shares(&signing_package, &signature_shares, pubkey_package));
+    if !culprits.is_empty() {
+        culprits.sort_unstable();
+        return Err(ThresholdError::ShareVerification { culprits });
+    }
+
+    let mut verified_shares = BTreeMap::new();
+    for (idx, share) in signature_shares {
+        verified_shares.insert(identifier(idx)?, share);
+    }
+
+    // Aggregate signature shares into final signature
+    let group_signature = frost::aggregate(&signing_package, &verified_shares, pubkey_package)
+        .map_err(|e| ThresholdError::Aggregation(format!("{:?}", e)))?;
+
+    let signature = group_signature.serialize()
+        .map_err(codec("Failed to serialize signature"))?;
+    let public_key = group_pubkey_package.verifying_key().serialize()
+        .map_err(codec("Failed to serialize verifying key"))?;
+    let randomized_public_key = match &randomized_package {
+        Some(package) => Some(package.verifying_key().serialize().map_err(codec("Failed to serialize verifying key"))?),
+        None => None,
+    };
+
+    Ok(CombinedSignature {
+        signature,
+        public_key,
+        randomized_public_key,
+    })
+}
+
+/// Describe a response that doesn't match the request sent to `signer_index`
+pub(crate) fn unexpected_response(signer_index: u16, response: SignerResponse) -> ThresholdError {
+    match response {
+        SignerResponse::Error { reason, .. } => ThresholdError::SignerRejected { signer_index, reason },
+        SignerResponse::UnknownNonces { .. } => ThresholdError::UnknownNonces { signer_index },
+        other => ThresholdError::Protocol(format!("Unexpected response from signer {}: {:?}", signer_index, other)),
+    }
+}
+
+/// Verify every signature share against its signer's verifying share
+///
+/// Returns the indices of all signers whose share is invalid (or who have no
+/// verifying share in `pubkey_package`), so they can be reported or excluded.
+/// An empty result means the shares are safe to aggregate.
+pub fn find_invalid_shares<C: Ciphersuite>(
+    signing_package: &frost::SigningPackage<C>,
+    signature_shares: &BTreeMap<u16, frost::round2::SignatureShare<C>>,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+) -> Vec<u16> {
+    signature_shares
+        .iter()
+        .filter(|(&idx, share)| {
+            let Ok(identifier) = identifier(idx) else {
+                return true;
+            };
+            let Some(verifying_share) = pubkey_package.verifying_shares().get(&identifier) else {
+                return true;
+            };
+            frost_core::verify_signature_share(
+                identifier,
+                verifying_share,
+                share,
+                signing_package,
+                pubkey_package.verifying_key(),
+            ).is_err()
+        })
+        .map(|(&idx, _)| idx)
+        .collect()
 }
 
 /// Generate FROST key packages for threshold signing
 ///
 /// Note: This uses the "trusted dealer" method for simplicity in this PoC.
-/// For production, implement the full DKG protocol which doesn't require a trusted party.
+/// For production, use `generate_frost_keys_dkg` which doesn't require a trusted party.
 /// The trusted dealer method still produces valid FROST threshold signatures.
-pub fn generate_frost_keys(
+pub fn generate_frost_keys<C: Ciphersuite>(
     max_signers: u16,
     min_signers: u16,
-) -> Result<(Vec<frost::keys::KeyPackage>, frost::keys::PublicKeyPackage), String> {
+) -> Result<(Vec<frost::keys::KeyPackage<C>>, frost::keys::PublicKeyPackage<C>), ThresholdError> {
     let mut rng = thread_rng();
 
     // Use trusted dealer for key generation (simpler but requires trust)
-    // For production DKG, use frost::keys::dkg module
+    // For production DKG, use generate_frost_keys_dkg
     let (shares, pubkey_package) = frost::keys::generate_with_dealer(
         max_signers,
         min_signers,
         frost::keys::IdentifierList::Default,
         &mut rng,
-    ).map_err(|e| format!("Trusted dealer keygen failed: {:?}", e))?;
+    ).map_err(|e| ThresholdError::KeyGeneration(format!("Trusted dealer keygen failed: {:?}", e)))?;
 
     // Convert secret shares to key packages
-    let key_packages: Vec<_> = shares
-        .into_iter()
-        .map(|(_, secret_share)| {
-            frost::keys::KeyPackage::try_from(secret_share)
-                .expect("Failed to create KeyPackage from SecretShare")
-        })
-        .collect();
+    let key_packages = shares
+        .into_values()
+        .map(frost::keys::KeyPackage::<C>::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ThresholdError::KeyGeneration(format!("Invalid secret share: {:?}", e)))?;
 
     Ok((key_packages, pubkey_package))
 }
 
-/// Full DKG implementation (currently not working - keeping for future fix)
-#[allow(dead_code)]
-fn generate_frost_keys_dkg(
+/// Generate FROST key packages with the trustless distributed key generation protocol
+///
+/// Runs a `DkgParticipant` for every party in-process and routes their serialized
+/// messages between them. Each participant derives its own `PublicKeyPackage`; these
+/// are cross-checked and the function fails if any participant disagrees on the group key.
+pub fn generate_frost_keys_dkg<C: Ciphersuite>(
     max_signers: u16,
     min_signers: u16,
-) -> Result<(Vec<frost::keys::KeyPackage>, frost::keys::PublicKeyPackage), String> {
-    use frost::keys::dkg::{part1, part2, part3};
-
-    let mut rng = thread_rng();
-    let max_signers_usize = max_signers as usize;
+) -> Result<(Vec<frost::keys::KeyPackage<C>>, frost::keys::PublicKeyPackage<C>), ThresholdError> {
+    let participants = (1..=max_signers)
+        .map(|i| DkgParticipant::new(i, max_signers, min_signers))
+        .collect::<Result<Vec<_>, ThresholdError>>()?;
 
-    // Part 1: Each participant generates their secret polynomial
-    let mut part1_packages = Vec::new();
-    let mut part1_secret_packages = Vec::new();
+    run_dkg_ceremony(participants)
+}
 
-    for i in 1..=max_signers {
-        let identifier = frost::Identifier::try_from(i)
-            .map_err(|e| format!("Invalid identifier: {:?}", e))?;
+/// Proactively refresh every participant's share without changing the group key
+///
+/// Runs a `DkgParticipant::for_refresh` for every holder in-process: each one deals
+/// a sharing of zero, so the new signing shares interpolate to the same secret while
+/// being unrelated to the old ones. `key_packages[i]` must belong to signer `i + 1`,
+/// and every signer in `pubkey_package` must take part. The old key packages must be
+/// discarded afterwards; they cannot be combined with the refreshed ones.
+pub fn refresh_frost_keys<C: Ciphersuite>(
+    key_packages: &[frost::keys::KeyPackage<C>],
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+) -> Result<(Vec<frost::keys::KeyPackage<C>>, frost::keys::PublicKeyPackage<C>), ThresholdError> {
+    if key_packages.len() != pubkey_package.verifying_shares().len() {
+        return Err(ThresholdError::InsufficientSigners {
+            required: pubkey_package.verifying_shares().len(),
+            available: key_packages.len(),
+        });
+    }
 
-        let (secret_package, package) = part1(
-            identifier,
-            max_signers,
-            min_signers,
-            &mut rng,
-        ).map_err(|e| format!("Part 1 failed: {:?}", e))?;
+    let participants = key_packages
+        .iter()
+        .enumerate()
+        .map(|(i, kp)| DkgParticipant::for_refresh((i + 1) as u16, kp.clone(), pubkey_package.clone()))
+        .collect::<Result<Vec<_>, ThresholdError>>()?;
 
-        part1_secret_packages.push(secret_package);
-        part1_packages.push(package);
+    let (new_key_packages, new_pubkey_package) = run_dkg_ceremony(participants)?;
+    if new_pubkey_package.verifying_key() != pubkey_package.verifying_key() {
+        return Err(ThresholdError::KeyGeneration("Refresh changed the group verifying key".to_string()));
     }
 
-    // Part 2: Each participant processes packages from others
-    let mut part2_packages = Vec::new();
-    let mut part2_secret_packages = Vec::new();
+    Ok((new_key_packages, new_pubkey_package))
+}
 
-    for i in 0..max_signers_usize {
-        let mut received_packages = BTreeMap::new();
-        for (j, package) in part1_packages.iter().enumerate() {
-            if i != j {
-                let sender_id = frost::Identifier::try_from((j + 1) as u16)
-                    .map_err(|e| format!("Invalid identifier: {:?}", e))?;
-                received_packages.insert(sender_id, package.clone());
-            }
+/// Reshare the group key to a new `(max_signers, min_signers)` committee
+///
+/// Every signer in `quorum` (at least the old threshold) deals its share to the new
+/// members 1..=`max_signers` with `deal_reshare`, and each member combines its sub-shares
+/// with a `ReshareRecipient`. The group verifying key, and therefore
+/// `CombinedSignature::public_key`, stays the same; old and new shares are unrelated.
+pub fn reshare_frost_keys<C: Ciphersuite>(
+    quorum: &[ThresholdSigner<C>],
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+    max_signers: u16,
+    min_signers: u16,
+) -> Result<(Vec<frost::keys::KeyPackage<C>>, frost::keys::PublicKeyPackage<C>), ThresholdError> {
+    let dealers: Vec<u16> = quorum.iter().map(|signer| signer.index).collect();
+
+    let mut recipients = (1..=max_signers)
+        .map(|i| ReshareRecipient::new(i, max_signers, min_signers, pubkey_package.clone(), &dealers))
+        .collect::<Result<Vec<_>, ThresholdError>>()?;
+
+    // Each dealer sends one private sub-share to every new member
+    let mut inboxes: BTreeMap<u16, Vec<Vec<u8>>> = BTreeMap::new();
+    for signer in quorum {
+        for (recipient, message) in deal_reshare(signer.index, &signer.key_package, &dealers, max_signers, min_signers)? {
+            inboxes.entry(recipient).or_default().push(message);
         }
+    }
 
-        let (secret_package, packages) = part2(
-            part1_secret_packages[i].clone(),
-            &received_packages,
-        ).map_err(|e| format!("Part 2 failed for participant {}: {:?}", i + 1, e))?;
-
-        // Debug: Verify part2 generated the right number of packages
-        let expected_packages = max_signers_usize - 1; // Should create packages for all OTHER participants
-        if packages.len() != expected_packages {
-            return Err(format!(
-                "Part 2 participant {} generated {} packages, expected {}",
-                i + 1,
-                packages.len(),
-                expected_packages
-            ));
+    let mut key_packages = Vec::with_capacity(recipients.len());
+    let mut new_pubkey_package: Option<frost::keys::PublicKeyPackage<C>> = None;
+    for recipient in recipients.iter_mut() {
+        let inbox = inboxes.remove(&recipient.index).unwrap_or_default();
+        recipient.receive(&inbox)?;
+        let (key_package, recipient_pubkey_package) = recipient.finish()?;
+
+        match &new_pubkey_package {
+            Some(expected) if *expected != recipient_pubkey_package => {
+                return Err(ThresholdError::KeyGeneration(format!(
+                    "Resharing participant {} derived a different public key package",
+                    recipient.index
+                )));
+            }
+            Some(_) => {}
+            None => new_pubkey_package = Some(recipient_pubkey_package),
         }
-
-        part2_secret_packages.push(secret_package);
-        part2_packages.push(packages);
+        key_packages.push(key_package);
     }
 
-    // Part 3: Each participant creates their key package
-    let mut key_packages = Vec::new();
-    let mut pubkey_packages = Vec::new();
+    let new_pubkey_package = new_pubkey_package.ok_or_else(|| {
+        ThresholdError::InvalidParameters("Resharing requires at least one new participant".to_string())
+    })?;
 
-    // Convert part1_packages to BTreeMap for part3
-    let part1_packages_map: BTreeMap<_, _> = part1_packages
-        .iter()
-        .enumerate()
-        .map(|(j, pkg)| {
-            let id = frost::Identifier::try_from((j + 1) as u16).unwrap();
-            (id, pkg.clone())
-        })
-        .collect();
+    Ok((key_packages, new_pubkey_package))
+}
 
-    for i in 0..max_signers_usize {
-        let my_id = frost::Identifier::try_from((i + 1) as u16)
-            .map_err(|e| format!("Invalid identifier: {:?}", e))?;
+/// Rebuild the lost key package of `participant` with the help of other signers
+///
+/// Runs the repairable threshold scheme in-process: every helper splits its share into
+/// random deltas for the other helpers (`repair_step1`), each helper sends only the sum
+/// of what it received to the participant (`repair_step2`), and the participant adds those
+/// up (`repair_step3`). No helper learns the repaired share. At least the threshold number
+/// of helpers is needed, and the result is checked against the participant's verifying
+/// share in `pubkey_package`.
+pub fn repair_frost_share<C: Ciphersuite>(
+    helpers: &[ThresholdSigner<C>],
+    participant: u16,
+    pubkey_package: &frost::keys::PublicKeyPackage<C>,
+) -> Result<frost::keys::KeyPackage<C>, ThresholdError> {
+    let helper_indices: Vec<u16> = helpers.iter().map(|helper| helper.index).collect();
+    let min_signers = helpers
+        .first()
+        .map(|helper| *helper.key_package.min_signers())
+        .ok_or(ThresholdError::InsufficientSigners { required: 1, available: 0 })?;
+
+    // Step 1: Each helper sends one delta to every helper, itself included
+    let mut inboxes: BTreeMap<u16, Vec<Vec<u8>>> = BTreeMap::new();
+    for helper in helpers {
+        for (recipient, message) in repair_step1(helper.index, &helper.key_package, &helper_indices, participant)? {
+            inboxes.entry(recipient).or_default().push(message);
+        }
+    }
 
-        // Collect Round 2 packages destined for this participant
-        let mut received_packages = BTreeMap::new();
-        for (j, packages) in part2_packages.iter().enumerate() {
-            let sender_id = frost::Identifier::try_from((j + 1) as u16)
-                .map_err(|e| format!("Invalid identifier: {:?}", e))?;
+    // Step 2: Each helper forwards only the sum of its deltas to the participant
+    let sigmas = helper_indices
+        .iter()
+        .map(|index| repair_step2::<C>(*index, participant, &inboxes.remove(index).unwrap_or_default()))
+        .collect::<Result<Vec<_>, ThresholdError>>()?;
 
-            // Don't include our own package
-            if i != j {
-                if let Some(package) = packages.get(&my_id) {
-                    received_packages.insert(sender_id, package.clone());
-                }
-            }
-        }
+    // Step 3: The participant rebuilds and checks its share
+    repair_step3(participant, min_signers, pubkey_package, &sigmas)
+}
 
-        // Debug: check we got the right number of packages
-        let expected_r2_count = max_signers_usize - 1;
-        if received_packages.len() != expected_r2_count {
-            return Err(format!(
-                "Participant {} expected {} round2 packages but got {}",
-                i + 1,
-                expected_r2_count,
-                received_packages.len()
-            ));
+/// Route the serialized messages of in-process DKG participants between them
+/// and cross-check the public key packages they derive
+fn run_dkg_ceremony<C: Ciphersuite>(
+    mut participants: Vec<DkgParticipant<C>>,
+) -> Result<(Vec<frost::keys::KeyPackage<C>>, frost::keys::PublicKeyPackage<C>), ThresholdError> {
+    // Part 1: Each participant generates their secret polynomial and broadcasts a commitment
+    let round1_messages = participants
+        .iter_mut()
+        .map(|p| p.start())
+        .collect::<Result<Vec<_>, ThresholdError>>()?;
+
+    // Part 2: Each participant processes everyone's round 1 messages and produces one
+    // private round 2 message per recipient
+    let mut inboxes: BTreeMap<u16, Vec<Vec<u8>>> = BTreeMap::new();
+    for participant in participants.iter_mut() {
+        for (recipient, message) in participant.receive_round1(&round1_messages)? {
+            inboxes.entry(recipient).or_default().push(message);
         }
+    }
 
-        // part1_packages_map should have ALL participants (including self)
-        if part1_packages_map.len() != max_signers_usize {
-            return Err(format!(
-                "Participant {} expected {} round1 packages but got {}",
-                i + 1,
-                max_signers_usize,
-                part1_packages_map.len()
-            ));
+    // Part 3: Each participant derives their key package from the messages addressed to them
+    let mut key_packages = Vec::with_capacity(participants.len());
+    let mut pubkey_package: Option<frost::keys::PublicKeyPackage<C>> = None;
+    for participant in participants.iter_mut() {
+        let inbox = inboxes.remove(&participant.index).unwrap_or_default();
+        participant.receive_round2(&inbox)?;
+        let (key_package, participant_pubkey_package) = participant.finish()?;
+
+        match &pubkey_package {
+            Some(expected) if *expected != participant_pubkey_package => {
+                return Err(ThresholdError::KeyGeneration(format!(
+                    "DKG participant {} derived a different public key package",
+                    participant.index
+                )));
+            }
+            Some(_) => {}
+            None => pubkey_package = Some(participant_pubkey_package),
         }
-
-        let (key_package, pubkey_package) = part3(
-            &part2_secret_packages[i],
-            &part1_packages_map,
-            &received_packages,
-        ).map_err(|e| format!("Part 3 failed for participant {}: {:?}. Round1 packages: {}, Round2 packages: {}",
-            i + 1, e, part1_packages_map.len(), received_packages.len()))?;
-
         key_packages.push(key_package);
-        pubkey_packages.push(pubkey_package);
     }
 
-    // All participants should have the same public key package
-    let pubkey_package = pubkey_packages[0].clone();
+    let pubkey_package = pubkey_package.ok_or_else(|| {
+        ThresholdError::InvalidParameters("DKG requires at least one participant".to_string())
+    })?;
 
     Ok((key_packages, pubkey_package))
 }
@@ -354,10 +1150,11 @@ fn generate_frost_keys_dkg(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
 
     #[test]
     fn test_frost_key_generation() {
-        let result = generate_frost_keys(5, 3);
+        let result = generate_frost_keys::<E>(5, 3);
         if let Err(e) = &result {
             eprintln!("Key generation error: {}", e);
         }
@@ -377,10 +1174,34 @@ mod tests {
     }
 
     #[test]
-    fn test_threshold_signing() {
-        let (key_packages, pubkey_package) = generate_frost_keys(5, 3).unwrap();
+    fn test_frost_key_generation_dkg() {
+        let result = generate_frost_keys_dkg::<E>(5, 3);
+        if let Err(e) = &result {
+            eprintln!("DKG key generation error: {}", e);
+        }
+        assert!(result.is_ok());
 
-        let signers: Vec<ThresholdSigner> = key_packages
+        let (key_packages, pubkey_package) = result.unwrap();
+        assert_eq!(key_packages.len(), 5);
+        assert_eq!(pubkey_package.verifying_shares().len(), 5);
+
+        // Every participant ends up with the same group key and a verifying share
+        // matching the public key package
+        for kp in &key_packages {
+            assert_eq!(kp.verifying_key(), pubkey_package.verifying_key());
+            assert_eq!(*kp.min_signers(), 3);
+            assert_eq!(
+                Some(kp.verifying_share()),
+                pubkey_package.verifying_shares().get(kp.identifier())
+            );
+        }
+    }
+
+    #[test]
+    fn test_threshold_signing_with_dkg_keys() {
+        let (key_packages, pubkey_package) = generate_frost_keys_dkg::<E>(5, 3).unwrap();
+
+        let signers: Vec<ThresholdSigner<E>> = key_packages
             .into_iter()
             .enumerate()
             .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
@@ -388,42 +1209,321 @@ mod tests {
 
         let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
 
-        let message = b"Hello, threshold signatures!";
-        let signer_indices = vec![1, 2, 3];
-
-        let result = coordinator.perform_threshold_signing(message, signer_indices);
-        if let Err(e) = &result {
-            eprintln!("Threshold signing error: {}", e);
-        }
-        assert!(result.is_ok());
-
-        let combined_sig = result.unwrap();
+        let message = b"Hello, trustless threshold signatures!";
+        let combined_sig = coordinator
+            .perform_threshold_signing(message, vec![2, 4, 5])
+            .unwrap();
 
-        // Verify the signature using ed25519-dalek
         use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 
-        let verifying_key = VerifyingKey::from_bytes(&combined_sig.public_key).unwrap();
-        let signature = Signature::from_bytes(&combined_sig.signature);
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
 
         assert!(verifying_key.verify(message, &signature).is_ok());
     }
 
     #[test]
-    fn test_threshold_signing_with_more_than_t_signers() {
-        let (key_packages, pubkey_package) = generate_frost_keys(5, 3).unwrap();
+    fn test_dkg_rejects_invalid_parameters() {
+        // min_signers larger than max_signers is rejected by part 1
+        assert!(generate_frost_keys_dkg::<E>(3, 5).is_err());
+        assert!(generate_frost_keys_dkg::<E>(0, 0).is_err());
+    }
+
+    #[test]
+    fn test_refresh_keeps_group_key_and_changes_shares() {
+        let (key_packages, pubkey_package) = generate_frost_keys_dkg::<E>(5, 3).unwrap();
+        let (refreshed, refreshed_pubkey_package) = refresh_frost_keys(&key_packages, &pubkey_package).unwrap();
+
+        assert_eq!(refreshed_pubkey_package.verifying_key(), pubkey_package.verifying_key());
+        for (old, new) in key_packages.iter().zip(&refreshed) {
+            assert_eq!(old.identifier(), new.identifier());
+            assert_eq!(new.verifying_key(), pubkey_package.verifying_key());
+            assert_ne!(old.signing_share(), new.signing_share());
+            assert_ne!(
+                pubkey_package.verifying_shares().get(old.identifier()),
+                refreshed_pubkey_package.verifying_shares().get(new.identifier())
+            );
+        }
 
-        let signers: Vec<ThresholdSigner> = key_packages
+        let signers: Vec<ThresholdSigner<E>> = refreshed
             .into_iter()
             .enumerate()
             .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
             .collect();
+        let mut coordinator = ThresholdCoordinator::new(3, signers, refreshed_pubkey_package);
 
-        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+        let message = b"Hello, refreshed threshold signatures!";
+        let combined_sig = coordinator
+            .perform_threshold_signing(message, vec![1, 3, 5])
+            .unwrap();
 
-        let message = b"Hello, threshold signatures!";
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 
-        // Try with 4 signers (more than threshold of 3)
-        let signer_indices = vec![1, 2, 3, 4];
+        // Still a valid signature under the original group key
+        let original_key = pubkey_package.verifying_key().serialize().unwrap();
+        assert_eq!(combined_sig.public_key.as_slice(), original_key.as_slice());
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+        assert!(verifying_key.verify(message, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_refresh_old_and_new_shares_cannot_be_mixed() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+        let (refreshed, refreshed_pubkey_package) = refresh_frost_keys(&key_packages, &pubkey_package).unwrap();
+
+        // Signers 1 and 2 refreshed, signer 3 still holds its leaked pre-refresh share
+        let mixed_signers = || -> Vec<ThresholdSigner<E>> {
+            vec![
+                ThresholdSigner::new(1, refreshed[0].clone()),
+                ThresholdSigner::new(2, refreshed[1].clone()),
+                ThresholdSigner::new(3, key_packages[2].clone()),
+            ]
+        };
+        let message = b"Hello, threshold signatures!";
+
+        let mut coordinator = ThresholdCoordinator::new(3, mixed_signers(), refreshed_pubkey_package);
+        assert_eq!(
+            coordinator.perform_threshold_signing(message, vec![1, 2, 3]),
+            Err(ThresholdError::ShareVerification { culprits: vec![3] })
+        );
+
+        // Against the old public key package it is the refreshed shares that no longer fit
+        let mut coordinator = ThresholdCoordinator::new(3, mixed_signers(), pubkey_package);
+        assert_eq!(
+            coordinator.perform_threshold_signing(message, vec![1, 2, 3]),
+            Err(ThresholdError::ShareVerification { culprits: vec![1, 2] })
+        );
+    }
+
+    #[test]
+    fn test_refresh_requires_every_key_package() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+
+        assert!(refresh_frost_keys(&key_packages[..2], &pubkey_package).is_err());
+
+        // Key packages must be ordered by signer index
+        let mut reordered = key_packages.clone();
+        reordered.swap(0, 1);
+        assert!(refresh_frost_keys(&reordered, &pubkey_package).is_err());
+    }
+
+    #[test]
+    fn test_reshare_to_larger_committee_keeps_group_key() {
+        let (key_packages, pubkey_package) = generate_frost_keys_dkg::<E>(5, 3).unwrap();
+        let quorum: Vec<ThresholdSigner<E>> = [1u16, 2, 5]
+            .iter()
+            .map(|&i| ThresholdSigner::new(i, key_packages[i as usize - 1].clone()))
+            .collect();
+
+        // 3-of-5 becomes 4-of-7
+        let (new_key_packages, new_pubkey_package) = reshare_frost_keys(&quorum, &pubkey_package, 7, 4).unwrap();
+        assert_eq!(new_key_packages.len(), 7);
+        assert_eq!(new_pubkey_package.verifying_shares().len(), 7);
+        assert_eq!(new_pubkey_package.verifying_key(), pubkey_package.verifying_key());
+
+        let signers: Vec<ThresholdSigner<E>> = new_key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        let mut coordinator = ThresholdCoordinator::new(4, signers, new_pubkey_package);
+
+        let message = b"Hello, reshared threshold signatures!";
+        // Three new signers are no longer enough
+        assert!(coordinator.perform_threshold_signing(message, vec![1, 6, 7]).is_err());
+        let combined_sig = coordinator
+            .perform_threshold_signing(message, vec![2, 3, 6, 7])
+            .unwrap();
+
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let original_key = pubkey_package.verifying_key().serialize().unwrap();
+        assert_eq!(combined_sig.public_key.as_slice(), original_key.as_slice());
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+        assert!(verifying_key.verify(message, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_reshare_requires_old_threshold() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+        let quorum: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .take(2)
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        assert!(reshare_frost_keys(&quorum, &pubkey_package, 3, 2).is_err());
+    }
+
+    #[test]
+    fn test_repair_lost_share_and_sign() {
+        let (key_packages, pubkey_package) = generate_frost_keys_dkg::<E>(5, 3).unwrap();
+        let helpers: Vec<ThresholdSigner<E>> = [1u16, 3, 4]
+            .iter()
+            .map(|&i| ThresholdSigner::new(i, key_packages[i as usize - 1].clone()))
+            .collect();
+
+        // Participant 2 lost its key package
+        let repaired = repair_frost_share(&helpers, 2, &pubkey_package).unwrap();
+        assert_eq!(repaired, key_packages[1]);
+
+        let signers = vec![
+            ThresholdSigner::new(1, key_packages[0].clone()),
+            ThresholdSigner::new(2, repaired),
+            ThresholdSigner::new(5, key_packages[4].clone()),
+        ];
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+        assert!(coordinator.perform_threshold_signing(b"Hello, repaired share!", vec![1, 2, 5]).is_ok());
+    }
+
+    #[test]
+    fn test_repair_detects_wrong_helper_share() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+        let mut helpers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .take(3)
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        assert!(repair_frost_share(&helpers[..2], 5, &pubkey_package).is_err());
+
+        // A helper using a share that does not belong to the group yields a share
+        // that fails the check against the stored verifying share
+        corrupt_signer(&mut helpers[1]);
+        assert!(repair_frost_share(&helpers, 5, &pubkey_package).is_err());
+    }
+
+    #[test]
+    fn test_preprocessed_nonce_is_used_once() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signer1 = ThresholdSigner::new(1, key_packages[0].clone());
+        let mut signer2 = ThresholdSigner::new(2, key_packages[1].clone());
+
+        let published1 = signer1.preprocess_nonces(3).unwrap();
+        let published2 = signer2.preprocess_nonces(1).unwrap();
+        assert_eq!(signer1.preprocessed_nonce_count(), 3);
+
+        let mut commitments = BTreeMap::new();
+        commitments.insert(*key_packages[0].identifier(), published1[1]);
+        commitments.insert(*key_packages[1].identifier(), published2[0]);
+        let signing_package = frost::SigningPackage::new(commitments, b"preprocessed")
+            .serialize()
+            .unwrap();
+        let request = |signer_index| encode::<E, _>(&SignerMessage::Round2Request {
+            signer_index,
+            signing_package: signing_package.clone(),
+        });
+
+        // No round 1: each signer finds its nonce from the commitment in the package
+        let share1 = signer1.receive_serialized_signing_request(&request(1));
+        let share2 = signer2.receive_serialized_signing_request(&request(2));
+        assert_eq!(signer1.preprocessed_nonce_count(), 2);
+        assert_eq!(signer2.preprocessed_nonce_count(), 0);
+
+        let coordinator = ThresholdCoordinator::new(2, Vec::new(), pubkey_package);
+        assert!(coordinator.combine_signatures(&signing_package, vec![share1, share2]).is_ok());
+
+        // The same commitment cannot be signed with again
+        let response = signer1.receive_serialized_signing_request(&request(1));
+        assert_eq!(decode::<E, SignerResponse>(&response), Ok(SignerResponse::UnknownNonces { signer_index: 1 }));
+        assert_eq!(signer1.preprocessed_nonce_count(), 2);
+
+        // Discarding drops only nonces still held: published1[1] is already used
+        let discard = encode::<E, _>(&SignerMessage::DiscardNoncesRequest {
+            signer_index: 1,
+            commitments: published1.iter().map(|c| c.serialize().unwrap()).collect(),
+        });
+        let response = signer1.receive_serialized_signing_request(&discard);
+        assert_eq!(
+            decode::<E, SignerResponse>(&response).unwrap(),
+            SignerResponse::NoncesDiscarded { signer_index: 1, discarded: 2 }
+        );
+        assert_eq!(signer1.preprocessed_nonce_count(), 0);
+    }
+
+    #[test]
+    fn test_preprocess_nonces_is_bounded() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signer = ThresholdSigner::new(1, key_packages[0].clone());
+
+        assert!(signer.preprocess_nonces(MAX_PREPROCESSED_NONCES + 1).is_err());
+        assert_eq!(signer.preprocessed_nonce_count(), 0);
+        assert_eq!(signer.preprocess_nonces(5).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_threshold_signing() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        let signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+
+        let message = b"Hello, threshold signatures!";
+        let signer_indices = vec![1, 2, 3];
+
+        let result = coordinator.perform_threshold_signing(message, signer_indices);
+        if let Err(e) = &result {
+            eprintln!("Threshold signing error: {}", e);
+        }
+        assert!(result.is_ok());
+
+        let combined_sig = result.unwrap();
+
+        // Verify the signature using ed25519-dalek
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+
+        assert!(verifying_key.verify(message, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_threshold_signing_rejects_duplicate_signers() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        let mut coordinator = ThresholdCoordinator::new(2, signers, pubkey_package);
+
+        // [1, 1, 2] must not quietly become a signing by [1, 2]
+        assert!(matches!(
+            coordinator.perform_threshold_signing(b"message", vec![1, 1, 2]),
+            Err(ThresholdError::InvalidParameters(_))
+        ));
+        assert!(matches!(
+            coordinator.perform_batch_signing(&[b"message"], vec![2, 2, 3]),
+            Err(ThresholdError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn test_threshold_signing_with_more_than_t_signers() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        let signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+
+        let message = b"Hello, threshold signatures!";
+
+        // Try with 4 signers (more than threshold of 3)
+        let signer_indices = vec![1, 2, 3, 4];
 
         let result = coordinator.perform_threshold_signing(message, signer_indices);
         if let Err(e) = &result {
@@ -438,17 +1538,496 @@ mod tests {
         // Verify the signature using ed25519-dalek
         use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 
-        let verifying_key = VerifyingKey::from_bytes(&combined_sig.public_key).unwrap();
-        let signature = Signature::from_bytes(&combined_sig.signature);
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+
+        assert!(verifying_key.verify(message, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_batch_signing_signs_every_message() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+        let signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+
+        let messages: Vec<Vec<u8>> = (0..10).map(|i| format!("message {}", i).into_bytes()).collect();
+        let results = coordinator.perform_batch_signing(&messages, vec![1, 3, 5]).unwrap();
+        assert_eq!(results.len(), messages.len());
+
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        for (message, result) in messages.iter().zip(results) {
+            let combined_sig = result.unwrap();
+            let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+            let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+            assert!(verifying_key.verify(message, &signature).is_ok());
+        }
+        // Every nonce of the batch was consumed, and the preprocessed pool never touched
+        assert!(coordinator.signers.iter().all(|s| s.batch_nonce_count() == 0));
+        assert!(coordinator.signers.iter().all(|s| s.preprocessed_nonce_count() == 0));
+
+        assert_eq!(coordinator.perform_batch_signing::<Vec<u8>>(&[], vec![1, 3, 5]), Ok(Vec::new()));
+        assert_eq!(
+            coordinator.perform_batch_signing(&messages, vec![1, 6]),
+            Err(ThresholdError::UnknownSigner(6))
+        );
+    }
+
+    /// Transport whose signer's batch responses for messages 0 and 1 are swapped
+    struct SwapFirstShares<'a>(InMemoryTransport<E, &'a mut ThresholdSigner<E>>);
+
+    impl SignerTransport for SwapFirstShares<'_> {
+        fn signer_index(&self) -> u16 {
+            self.0.signer_index()
+        }
+
+        fn exchange(&mut self, request: &SignerMessage) -> Result<SignerResponse, ThresholdError> {
+            self.0.exchange(request)
+        }
+
+        fn send_signing_packages(&mut self, signing_packages: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, ThresholdError> {
+            let mut responses = self.0.send_signing_packages(signing_packages)?;
+            responses.swap(0, 1);
+            Ok(responses)
+        }
+    }
+
+    #[test]
+    fn test_batch_signing_reports_per_message_failures() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let (first, rest) = signers.split_at_mut(1);
+        let mut honest = InMemoryTransport::new(&mut first[0]);
+        let mut cheating = SwapFirstShares(InMemoryTransport::new(&mut rest[0]));
+        let mut endpoints: Vec<&mut dyn SignerTransport> = vec![&mut honest, &mut cheating];
+
+        let messages = [b"zero".as_slice(), b"one", b"two"];
+        let results = sign_batch_with_transports(2, &pubkey_package, &messages, &mut endpoints).unwrap();
+
+        // Signer 2's shares for messages 0 and 1 land in the wrong sessions
+        assert_eq!(results[0], Err(ThresholdError::ShareVerification { culprits: vec![2] }));
+        assert_eq!(results[1], Err(ThresholdError::ShareVerification { culprits: vec![2] }));
+        assert!(results[2].is_ok());
+    }
+
+    /// Transport whose batch round 2 requests are lost, except empty ones
+    struct LosesBatchRound2<'a>(InMemoryTransport<E, &'a mut ThresholdSigner<E>>);
+
+    impl SignerTransport for LosesBatchRound2<'_> {
+        fn signer_index(&self) -> u16 {
+            self.0.signer_index()
+        }
+
+        fn exchange(&mut self, request: &SignerMessage) -> Result<SignerResponse, ThresholdError> {
+            self.0.exchange(request)
+        }
+
+        fn send_signing_packages(&mut self, signing_packages: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, ThresholdError> {
+            if !signing_packages.is_empty() {
+                return Err(ThresholdError::Transport {
+                    signer_index: self.signer_index(),
+                    reason: "connection reset".to_string(),
+                });
+            }
+            self.0.send_signing_packages(signing_packages)
+        }
+    }
+
+    /// Transport whose round 2 replies arrive garbled
+    struct GarblesRound2<'a>(InMemoryTransport<E, &'a mut ThresholdSigner<E>>);
+
+    impl SignerTransport for GarblesRound2<'_> {
+        fn signer_index(&self) -> u16 {
+            self.0.signer_index()
+        }
+
+        fn exchange(&mut self, request: &SignerMessage) -> Result<SignerResponse, ThresholdError> {
+            let response = self.0.exchange(request)?;
+            match request {
+                SignerMessage::Round2Request { .. } => Err(ThresholdError::Codec("Not an enveloped message".to_string())),
+                _ => Ok(response),
+            }
+        }
+    }
+
+    #[test]
+    fn test_undecodable_round2_reply_is_a_transport_failure() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let (first, rest) = signers.split_at_mut(1);
+        let mut honest = InMemoryTransport::new(&mut first[0]);
+        let mut garbled = GarblesRound2(InMemoryTransport::new(&mut rest[0]));
+        let mut endpoints: Vec<&mut dyn SignerTransport> = vec![&mut honest, &mut garbled];
+
+        // Signer 2 is not blamed for a reply that never arrived intact
+        let result = sign_with_transports(2, &pubkey_package, b"message", &mut endpoints);
+        assert!(matches!(result, Err(ThresholdError::Transport { signer_index: 2, .. })));
+    }
+
+    #[test]
+    fn test_failed_batch_releases_nonces() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let (first, rest) = signers.split_at_mut(1);
+        let (second, third) = rest.split_at_mut(1);
+        let mut failing = LosesBatchRound2(InMemoryTransport::new(&mut first[0]));
+        let mut second = InMemoryTransport::new(&mut second[0]);
+        let mut third = InMemoryTransport::new(&mut third[0]);
+        let mut endpoints: Vec<&mut dyn SignerTransport> = vec![&mut failing, &mut second, &mut third];
+
+        // Signer 1 fails in round 2, after every signer has generated its batch nonces
+        let messages = [b"zero".as_slice(), b"one", b"two"];
+        let result = sign_batch_with_transports(2, &pubkey_package, &messages, &mut endpoints);
+        assert!(matches!(result, Err(ThresholdError::Transport { signer_index: 1, .. })));
+
+        // No nonce is left behind to count against the signers' preprocessing limit
+        for signer in &signers {
+            assert_eq!(signer.batch_nonce_count(), 0);
+            assert_eq!(signer.preprocessed_nonce_count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_perform_threshold_signing_identifies_invalid_share() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        corrupt_signer(&mut signers[3]);
+        corrupt_signer(&mut signers[4]);
+
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+
+        let message = b"Hello, threshold signatures!";
+
+        let result = coordinator.perform_threshold_signing(message, vec![1, 2, 4, 5]);
+        assert_eq!(result, Err(ThresholdError::ShareVerification { culprits: vec![4, 5] }));
+
+        // The honest signers alone still produce a valid signature
+        assert!(coordinator.perform_threshold_signing(message, vec![1, 2, 3]).is_ok());
+    }
+
+    #[test]
+    fn test_find_invalid_shares_reports_unknown_signer() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .take(2)
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let mut commitments = BTreeMap::new();
+        for signer in signers.iter_mut() {
+            let identifier = frost::Identifier::try_from(signer.index).unwrap();
+            commitments.insert(identifier, signer.round1_generate_nonces());
+        }
+        let signing_package = frost::SigningPackage::new(commitments, b"message");
+        let share1 = signers[0].round2_sign(b"message", &signing_package).unwrap();
+        let share2 = signers[1].round2_sign(b"message", &signing_package).unwrap();
+
+        // A share claimed by index 7 has no verifying share in a 3-signer package
+        let shares = BTreeMap::from([(1, share1), (7, share2)]);
+        assert_eq!(find_invalid_shares(&signing_package, &shares, &pubkey_package), vec![7]);
+    }
+
+    #[test]
+    fn test_robust_signing_excludes_cheaters_and_retries() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        corrupt_signer(&mut signers[1]);
+        corrupt_signer(&mut signers[3]);
+
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+
+        let message = b"Hello, robust threshold signatures!";
+
+        // threshold + 2 signers, two of whom cheat
+        let (combined_sig, report) = coordinator
+            .perform_robust_threshold_signing(message, vec![1, 2, 3, 4, 5])
+            .unwrap();
+
+        assert_eq!(report.excluded_signers, vec![2, 4]);
+        assert_eq!(report.signers_used, vec![1, 3, 5]);
+        assert_eq!(report.attempts, 2);
+
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+
+        assert!(verifying_key.verify(message, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_robust_signing_without_cheaters_uses_single_attempt() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        let signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+
+        let (_, report) = coordinator
+            .perform_robust_threshold_signing(b"message", vec![1, 2, 3, 4])
+            .unwrap();
+
+        assert!(report.excluded_signers.is_empty());
+        assert_eq!(report.signers_used, vec![1, 2, 3, 4]);
+        assert_eq!(report.attempts, 1);
+    }
+
+    #[test]
+    fn test_robust_signing_fails_when_too_few_honest_signers() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        corrupt_signer(&mut signers[0]);
+        corrupt_signer(&mut signers[2]);
+
+        let mut coordinator = ThresholdCoordinator::new(3, signers, pubkey_package);
+
+        // Only signers 2 and 4 are honest, below the threshold of 3
+        let result = coordinator.perform_robust_threshold_signing(b"message", vec![1, 2, 3, 4]);
+        assert_eq!(result, Err(ThresholdError::ShareVerification { culprits: vec![1, 3] }));
+    }
+
+    #[test]
+    fn test_combine_signatures_from_serialized_protocol_messages() {
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        // Signers driven only through serialized bytes, as an out-of-process signer would be
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+        let coordinator = ThresholdCoordinator::new(3, Vec::new(), pubkey_package);
+
+        let message = b"Hello, serialized threshold signatures!";
+        let participants = [1u16, 3, 5];
+
+        let mut commitments = BTreeMap::new();
+        for &idx in &participants {
+            let request = encode::<E, _>(&SignerMessage::Round1Request { signer_index: idx });
+            let response = signers[(idx - 1) as usize].receive_serialized_signing_request(&request);
+            let SignerResponse::Commitments { signer_index, commitments: bytes } = decode::<E, _>(&response).unwrap() else {
+                panic!("Expected commitments from signer {}", idx);
+            };
+            assert_eq!(signer_index, idx);
+            commitments.insert(
+                frost::Identifier::try_from(idx).unwrap(),
+                frost::round1::SigningCommitments::<E>::deserialize(&bytes).unwrap(),
+            );
+        }
+
+        let signing_package = frost::SigningPackage::new(commitments, message).serialize().unwrap();
+
+        let shares: Vec<Vec<u8>> = participants
+            .iter()
+            .map(|&idx| {
+                let request = encode::<E, _>(&SignerMessage::Round2Request {
+                    signer_index: idx,
+                    signing_package: signing_package.clone(),
+                });
+                signers[(idx - 1) as usize].receive_serialized_signing_request(&request)
+            })
+            .collect();
+
+        let combined_sig = coordinator.combine_signatures(&signing_package, shares).unwrap();
+
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
 
         assert!(verifying_key.verify(message, &signature).is_ok());
     }
 
+    #[test]
+    fn test_signer_rejects_misaddressed_request() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signer = ThresholdSigner::new(1, key_packages[0].clone());
+
+        let request = encode::<E, _>(&SignerMessage::Round1Request { signer_index: 2 });
+        let response: SignerResponse = decode::<E, _>(&signer.receive_serialized_signing_request(&request)).unwrap();
+
+        assert!(matches!(response, SignerResponse::Error { signer_index: 1, .. }));
+    }
+
+    #[test]
+    fn test_signer_answers_malformed_request_with_error() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signer = ThresholdSigner::new(1, key_packages[0].clone());
+
+        let mut trailing = encode::<E, _>(&SignerMessage::Round1Request { signer_index: 1 });
+        trailing.push(0);
+        // A response sent back to the signer is refused by its envelope type
+        let misrouted = encode::<E, _>(&SignerResponse::Commitments { signer_index: 1, commitments: vec![] });
+        for request in [vec![], vec![0xff; 7], trailing, misrouted] {
+            let response: SignerResponse = decode::<E, _>(&signer.receive_serialized_signing_request(&request)).unwrap();
+            assert!(matches!(response, SignerResponse::Error { signer_index: 1, .. }));
+        }
+        // No nonces were generated for the rejected requests
+        assert!(signer.signing_commitments.is_none());
+    }
+
+    #[test]
+    fn test_signer_round2_without_round1_returns_error() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let mut commitments = BTreeMap::new();
+        for signer in signers.iter_mut().take(2) {
+            let identifier = frost::Identifier::try_from(signer.index).unwrap();
+            commitments.insert(identifier, signer.round1_generate_nonces());
+        }
+        let signing_package = frost::SigningPackage::new(commitments, b"message").serialize().unwrap();
+
+        // Signer 3 never ran round 1, so it has no nonces to sign with
+        let request = encode::<E, _>(&SignerMessage::Round2Request {
+            signer_index: 3,
+            signing_package,
+        });
+        let response: SignerResponse = decode::<E, _>(&signers[2].receive_serialized_signing_request(&request)).unwrap();
+
+        assert!(matches!(response, SignerResponse::Error { signer_index: 3, .. }));
+    }
+
+    /// Two signers that ran round 1, and the package for `message` built from their commitments
+    fn signers_after_round1(message: &[u8]) -> (Vec<ThresholdSigner<E>>, frost::SigningPackage<E>) {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut signers: Vec<ThresholdSigner<E>> = key_packages
+            .into_iter()
+            .enumerate()
+            .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
+            .collect();
+
+        let mut commitments = BTreeMap::new();
+        for signer in signers.iter_mut().take(2) {
+            commitments.insert(*signer.key_package.identifier(), signer.round1_generate_nonces());
+        }
+        (signers, frost::SigningPackage::new(commitments, message))
+    }
+
+    #[test]
+    fn test_round2_consumes_nonces() {
+        let (mut signers, signing_package) = signers_after_round1(b"message");
+
+        assert!(signers[0].round2_sign(b"message", &signing_package).is_ok());
+        assert!(signers[0].signing_nonces.is_none());
+        assert!(signers[0].signing_commitments.is_none());
+
+        // Replaying the exact same package must not produce a second share
+        assert!(signers[0].round2_sign(b"message", &signing_package).is_err());
+    }
+
+    #[test]
+    fn test_nonce_reuse_attack_is_refused() {
+        let (mut signers, signing_package) = signers_after_round1(b"pay 1 coin to alice");
+        assert!(signers[0].round2_sign(b"pay 1 coin to alice", &signing_package).is_ok());
+
+        // A malicious coordinator reuses the same commitments for a second message.
+        // Two shares z1 = d + e*rho1 + lambda*s*c1 and z2 = d + e*rho2 + lambda*s*c2 under
+        // one nonce pair would let it solve for the secret share s.
+        let second_package = frost::SigningPackage::new(
+            signing_package.signing_commitments().clone(),
+            b"pay 1000 coins to mallory",
+        );
+        let result = signers[0].round2_sign(b"pay 1000 coins to mallory", &second_package);
+        assert!(result.is_err());
+
+        // Same attack over the serialized protocol
+        let request = encode::<E, _>(&SignerMessage::Round2Request {
+            signer_index: 1,
+            signing_package: second_package.serialize().unwrap(),
+        });
+        let response: SignerResponse = decode::<E, _>(&signers[0].receive_serialized_signing_request(&request)).unwrap();
+        assert_eq!(response, SignerResponse::UnknownNonces { signer_index: 1 });
+    }
+
+    #[test]
+    fn test_round2_requires_own_commitment_in_package() {
+        let (mut signers, signing_package) = signers_after_round1(b"message");
+
+        // Package built from signers 2 and 3 only, sent to signer 1
+        let commitment3 = signers[2].round1_generate_nonces();
+        let mut commitments = signing_package.signing_commitments().clone();
+        commitments.remove(signers[0].key_package.identifier());
+        commitments.insert(*signers[2].key_package.identifier(), commitment3);
+        let without_signer1 = frost::SigningPackage::new(commitments.clone(), b"message");
+        assert!(signers[0].round2_sign(b"message", &without_signer1).is_err());
+
+        // Package with someone else's commitment under signer 1's identifier
+        commitments.insert(*signers[0].key_package.identifier(), commitment3);
+        let forged = frost::SigningPackage::new(commitments, b"message");
+        assert!(signers[0].round2_sign(b"message", &forged).is_err());
+
+        // A package for another message than the one being signed
+        assert!(signers[0].round2_sign(b"other message", &signing_package).is_err());
+
+        // None of the refused packages consumed signer 1's nonces
+        assert!(signers[0].signing_nonces.is_some());
+        assert!(signers[0].round2_sign(b"message", &signing_package).is_ok());
+    }
+
+    /// Replace a signer's secret share with one from an unrelated key set, so its
+    /// signature shares no longer match its verifying share
+    fn corrupt_signer(signer: &mut ThresholdSigner<E>) {
+        let (other_key_packages, _) = generate_frost_keys::<E>(5, 3).unwrap();
+        let kp = &signer.key_package;
+        signer.key_package = frost::keys::KeyPackage::new(
+            *kp.identifier(),
+            *other_key_packages[0].signing_share(),
+            *kp.verifying_share(),
+            *kp.verifying_key(),
+            *kp.min_signers(),
+        );
+    }
+
     #[test]
     fn test_threshold_signing_with_one_invalid_share() {
-        let (key_packages, pubkey_package) = generate_frost_keys(5, 3).unwrap();
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
 
-        let signers: Vec<ThresholdSigner> = key_packages
+        let signers: Vec<ThresholdSigner<E>> = key_packages
             .into_iter()
             .enumerate()
             .map(|(i, kp)| ThresholdSigner::new((i + 1) as u16, kp))
@@ -475,7 +2054,7 @@ mod tests {
         let mut signature_shares = BTreeMap::new();
         for (i, &idx) in signer_indices.iter().enumerate() {
             let identifier = frost::Identifier::try_from(idx).unwrap();
-            let signer = &coordinator.signers[(idx - 1) as usize];
+            let signer = &mut coordinator.signers[(idx - 1) as usize];
 
             if i == 3 {
                 // Create an invalid share by using wrong message
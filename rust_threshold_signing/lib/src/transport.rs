@@ -0,0 +1,353 @@
+use frost_core::Ciphersuite;
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+#[cfg(feature = "tcp")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "tcp")]
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(feature = "tcp")]
+use std::time::Duration;
+
+use crate::derivation::{ChainCode, DerivationPath};
+use crate::error::ThresholdError;
+use crate::serialization::{SignerMessage, SignerResponse, decode, encode};
+use crate::threshold::{ThresholdSigner, unexpected_response};
+
+/// Largest frame accepted from a peer; bounds the allocation for the length prefix
+pub const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+/// Default read/write timeout for `TcpTransport`
+#[cfg(feature = "tcp")]
+pub const DEFAULT_TCP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How the coordinator talks to one signer
+///
+/// The signing logic only ever goes through these calls, so a signer can live in
+/// the same process, another process, or another machine. A transport only has
+/// to deliver one `SignerMessage` and return the signer's `SignerResponse`
+/// (`exchange`); every request of the protocol is built on top of that.
+pub trait SignerTransport {
+    /// Identifier (1-based index) of the signer at the other end
+    fn signer_index(&self) -> u16;
+
+    /// Send one request to the signer and wait for its response
+    fn exchange(&mut self, request: &SignerMessage) -> Result<SignerResponse, ThresholdError>;
+
+    /// Round 1: request fresh nonce commitments
+    fn send_round1_request(&mut self) -> Result<SignerResponse, ThresholdError> {
+        let signer_index = self.signer_index();
+        self.exchange(&SignerMessage::Round1Request { signer_index })
+    }
+
+    /// Round 2: have the signer sign the serialized `frost::SigningPackage` and
+    /// return its response
+    fn send_signing_package(&mut self, signing_package: &[u8]) -> Result<SignerResponse, ThresholdError> {
+        let signer_index = self.signer_index();
+        self.exchange(&SignerMessage::Round2Request {
+            signer_index,
+            signing_package: signing_package.to_vec(),
+        })
+    }
+
+    /// Round 2 of rerandomized signing: like `send_signing_package`, together with
+    /// the serialized `Randomizer` (see `rerandomized`)
+    fn send_randomized_signing_package(
+        &mut self,
+        signing_package: &[u8],
+        randomizer: &[u8],
+    ) -> Result<SignerResponse, ThresholdError> {
+        let signer_index = self.signer_index();
+        self.exchange(&SignerMessage::RandomizedRound2Request {
+            signer_index,
+            signing_package: signing_package.to_vec(),
+            randomizer: randomizer.to_vec(),
+        })
+    }
+
+    /// Round 2 at a derivation path: like `send_signing_package`, together with
+    /// the chain code and path of the child key to sign for (see `derivation`)
+    fn send_derived_signing_package(
+        &mut self,
+        signing_package: &[u8],
+        chain_code: &ChainCode,
+        path: &DerivationPath,
+    ) -> Result<SignerResponse, ThresholdError> {
+        let signer_index = self.signer_index();
+        self.exchange(&SignerMessage::DerivedRound2Request {
+            signer_index,
+            signing_package: signing_package.to_vec(),
+            chain_code: *chain_code,
+            path: path.indices().to_vec(),
+        })
+    }
+
+    /// Preprocessing: ask for `count` pregenerated nonce commitments
+    fn send_preprocess_request(&mut self, count: u16) -> Result<SignerResponse, ThresholdError> {
+        let signer_index = self.signer_index();
+        self.exchange(&SignerMessage::PreprocessRequest { signer_index, count })
+    }
+
+    /// Batch round 1: ask for `count` fresh nonce commitments, one per message
+    fn send_batch_round1_request(&mut self, count: u16) -> Result<SignerResponse, ThresholdError> {
+        let signer_index = self.signer_index();
+        self.exchange(&SignerMessage::BatchRound1Request { signer_index, count })
+    }
+
+    /// Batch round 2: deliver several serialized `frost::SigningPackage`s in one
+    /// request and return one serialized `SignerResponse` per package, in order
+    fn send_signing_packages(&mut self, signing_packages: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, ThresholdError> {
+        let signer_index = self.signer_index();
+        let response = self.exchange(&SignerMessage::BatchRound2Request {
+            signer_index,
+            signing_packages: signing_packages.to_vec(),
+        })?;
+        batch_responses(signer_index, response, signing_packages.len())
+    }
+
+    /// Ask the signer to drop the preprocessed nonces behind these serialized
+    /// commitments without signing
+    fn send_discard_request(&mut self, commitments: &[Vec<u8>]) -> Result<SignerResponse, ThresholdError> {
+        let signer_index = self.signer_index();
+        self.exchange(&SignerMessage::DiscardNoncesRequest {
+            signer_index,
+            commitments: commitments.to_vec(),
+        })
+    }
+}
+
+/// Transport to a signer living in the same process
+///
+/// Requests still go through the serialized protocol, so this behaves exactly
+/// like a remote signer. Works with an owned `ThresholdSigner` or a `&mut` one.
+pub struct InMemoryTransport<C: Ciphersuite, S: BorrowMut<ThresholdSigner<C>> = ThresholdSigner<C>> {
+    signer: S,
+    ciphersuite: PhantomData<C>,
+}
+
+impl<C: Ciphersuite, S: BorrowMut<ThresholdSigner<C>>> InMemoryTransport<C, S> {
+    pub fn new(signer: S) -> Self {
+        Self {
+            signer,
+            ciphersuite: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.signer
+    }
+}
+
+impl<C: Ciphersuite, S: BorrowMut<ThresholdSigner<C>>> SignerTransport for InMemoryTransport<C, S> {
+    fn signer_index(&self) -> u16 {
+        self.signer.borrow().index
+    }
+
+    fn exchange(&mut self, request: &SignerMessage) -> Result<SignerResponse, ThresholdError> {
+        let response = self.signer.borrow_mut().receive_serialized_signing_request(&encode::<C, _>(request));
+        decode::<C, _>(&response)
+    }
+}
+
+/// Transport to a signer reachable over TCP
+///
+/// Every `SignerMessage` and `SignerResponse` is sent as one length-prefixed
+/// frame (see `write_frame`). The remote end is expected to run `serve_signer`
+/// with a signer of the same ciphersuite `C`.
+#[cfg(feature = "tcp")]
+pub struct TcpTransport<C: Ciphersuite> {
+    signer_index: u16,
+    stream: TcpStream,
+    ciphersuite: PhantomData<C>,
+}
+
+#[cfg(feature = "tcp")]
+impl<C: Ciphersuite> TcpTransport<C> {
+    /// Connect to the signer with the given index at `addr`
+    pub fn connect<A: ToSocketAddrs>(signer_index: u16, addr: A) -> Result<Self, ThresholdError> {
+        Self::connect_with_timeout(signer_index, addr, DEFAULT_TCP_TIMEOUT)
+    }
+
+    /// Connect and use `timeout` for every read and write on the connection
+    pub fn connect_with_timeout<A: ToSocketAddrs>(
+        signer_index: u16,
+        addr: A,
+        timeout: Duration,
+    ) -> Result<Self, ThresholdError> {
+        let stream = TcpStream::connect(addr)
+            .map_err(|e| transport_error(signer_index, "connect", e))?;
+        stream.set_read_timeout(Some(timeout))
+            .and_then(|_| stream.set_write_timeout(Some(timeout)))
+            .and_then(|_| stream.set_nodelay(true))
+            .map_err(|e| transport_error(signer_index, "configure", e))?;
+
+        Ok(Self {
+            signer_index,
+            stream,
+            ciphersuite: PhantomData,
+        })
+    }
+
+    fn send(&mut self, request: &SignerMessage) -> Result<(), ThresholdError> {
+        write_frame(&mut self.stream, &encode::<C, _>(request))
+            .map_err(|e| transport_error(self.signer_index, "send", e))
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, ThresholdError> {
+        read_frame(&mut self.stream)
+            .map_err(|e| transport_error(self.signer_index, "receive", e))
+    }
+}
+
+#[cfg(feature = "tcp")]
+pub(crate) fn transport_error(signer_index: u16, action: &str, error: io::Error) -> ThresholdError {
+    ThresholdError::Transport {
+        signer_index,
+        reason: format!("{} failed: {}", action, error),
+    }
+}
+
+#[cfg(feature = "tcp")]
+impl<C: Ciphersuite> SignerTransport for TcpTransport<C> {
+    fn signer_index(&self) -> u16 {
+        self.signer_index
+    }
+
+    fn exchange(&mut self, request: &SignerMessage) -> Result<SignerResponse, ThresholdError> {
+        self.send(request)?;
+        decode::<C, _>(&self.receive()?)
+    }
+}
+
+/// Unpack a `BatchSignatureShares` response carrying one entry per signing package
+fn batch_responses(signer_index: u16, response: SignerResponse, expected: usize) -> Result<Vec<Vec<u8>>, ThresholdError> {
+    match response {
+        SignerResponse::BatchSignatureShares { signer_index: from, responses }
+            if from == signer_index && responses.len() == expected => Ok(responses),
+        other => Err(unexpected_response(signer_index, other)),
+    }
+}
+
+/// Write one frame: a big-endian `u32` length followed by the payload
+#[cfg(feature = "tcp")]
+pub fn write_frame<W: Write>(writer: &mut W, payload: &[u8]) -> io::Result<()> {
+    if payload.len() > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Frame too large"));
+    }
+    writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Read one frame written by `write_frame`, rejecting frames over `MAX_FRAME_SIZE`
+#[cfg(feature = "tcp")]
+pub fn read_frame<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut len_bytes = [0u8; 4];
+    reader.read_exact(&mut len_bytes)?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Frame too large"));
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+/// Answer protocol requests arriving on `stream` until the peer disconnects
+///
+/// Works with any byte stream (`TcpStream`, `UnixStream`, ...).
+#[cfg(feature = "tcp")]
+pub fn serve_signer<C: Ciphersuite, S: Read + Write>(signer: &mut ThresholdSigner<C>, mut stream: S) -> io::Result<()> {
+    loop {
+        let request = match read_frame(&mut stream) {
+            Ok(request) => request,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let response = signer.receive_serialized_signing_request(&request);
+        write_frame(&mut stream, &response)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frost_ed25519::Ed25519Sha512 as E;
+    use crate::threshold::generate_frost_keys;
+
+    #[cfg(feature = "tcp")]
+    #[test]
+    fn test_frame_roundtrip_and_size_limit() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, b"hello").unwrap();
+        assert_eq!(read_frame(&mut buffer.as_slice()).unwrap(), b"hello");
+
+        let mut oversized = ((MAX_FRAME_SIZE + 1) as u32).to_be_bytes().to_vec();
+        oversized.extend_from_slice(&[0u8; 16]);
+        assert!(read_frame(&mut oversized.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_in_memory_transport_exchanges_protocol_messages() {
+        let (key_packages, _) = generate_frost_keys::<E>(3, 2).unwrap();
+        let mut transport = InMemoryTransport::new(ThresholdSigner::new(1, key_packages[0].clone()));
+
+        assert!(matches!(
+            transport.send_round1_request(),
+            Ok(SignerResponse::Commitments { signer_index: 1, .. })
+        ));
+        // The signer's refusal comes back as a response, not a transport failure
+        assert!(matches!(
+            transport.exchange(&SignerMessage::Round1Request { signer_index: 2 }),
+            Ok(SignerResponse::Error { signer_index: 1, .. })
+        ));
+    }
+
+    #[cfg(feature = "tcp")]
+    #[test]
+    fn test_threshold_signing_over_tcp() {
+        use crate::threshold::ThresholdCoordinator;
+        use std::net::TcpListener;
+        use std::thread;
+
+        let (key_packages, pubkey_package) = generate_frost_keys::<E>(5, 3).unwrap();
+
+        // Each signer runs in its own thread behind its own TCP listener
+        let mut transports: Vec<TcpTransport<E>> = Vec::new();
+        let mut servers = Vec::new();
+        for (i, kp) in key_packages.into_iter().enumerate().take(3) {
+            let index = (i + 1) as u16;
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            servers.push(thread::spawn(move || {
+                let mut signer = ThresholdSigner::new(index, kp);
+                let (stream, _) = listener.accept().unwrap();
+                serve_signer(&mut signer, stream).unwrap();
+            }));
+            transports.push(TcpTransport::connect(index, addr).unwrap());
+        }
+
+        let coordinator = ThresholdCoordinator::new(3, Vec::new(), pubkey_package);
+
+        let message = b"Hello, networked threshold signatures!";
+        let mut endpoints: Vec<&mut dyn SignerTransport> = transports
+            .iter_mut()
+            .map(|t| t as &mut dyn SignerTransport)
+            .collect();
+        let combined_sig = coordinator
+            .perform_threshold_signing_with_transports(message, &mut endpoints)
+            .unwrap();
+
+        drop(endpoints);
+        drop(transports);
+        for server in servers {
+            server.join().unwrap();
+        }
+
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let verifying_key = VerifyingKey::from_bytes(combined_sig.public_key.as_slice().try_into().unwrap()).unwrap();
+        let signature = Signature::from_slice(&combined_sig.signature).unwrap();
+
+        assert!(verifying_key.verify(message, &signature).is_ok());
+    }
+}
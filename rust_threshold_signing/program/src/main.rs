@@ -1,7 +1,8 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use threshold_signing_lib::{CombinedSignature, deserialize};
+use threshold_signing_lib::ciphersuite::Ed25519Sha512;
+use threshold_signing_lib::{CombinedSignature, decode};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 
 pub fn main() {
@@ -9,18 +10,46 @@ pub fn main() {
     let message = sp1_zkvm::io::read::<Vec<u8>>();
     let combined_sig_bytes = sp1_zkvm::io::read::<Vec<u8>>();
 
-    // Deserialize the combined signature
-    let combined_sig: CombinedSignature = deserialize(&combined_sig_bytes);
+    // The signature bytes are untrusted: a malformed or mistyped envelope, or an
+    // invalid public key, proves "invalid" (with all-zero keys) instead of aborting
+    let (is_valid, group_key, randomized_key) = match decode::<Ed25519Sha512, CombinedSignature>(&combined_sig_bytes) {
+        Ok(combined_sig) => match keys(&combined_sig) {
+            Some((group_key, randomized_key)) => {
+                let verifying_key = randomized_key.unwrap_or(group_key);
+                (verify(&message, &verifying_key, &combined_sig.signature), group_key, randomized_key)
+            }
+            None => (false, [0u8; 32], None),
+        },
+        Err(_) => (false, [0u8; 32], None),
+    };
 
-    // Verify the signature inside zkVM
-    let verifying_key = VerifyingKey::from_bytes(&combined_sig.public_key)
-        .expect("Invalid public key");
-    let signature = Signature::from_bytes(&combined_sig.signature);
-
-    let is_valid = verifying_key.verify(&message, &signature).is_ok();
-
-    // Write verification result to public output
+    // Write verification result to public output. The group key is committed on its own
+    // so a verifier can compare it with the key it knows; a rerandomized signature
+    // verifies under the one-time key committed next (all zero when there is none).
     sp1_zkvm::io::commit(&is_valid);
-    sp1_zkvm::io::commit(&combined_sig.public_key);
+    sp1_zkvm::io::commit(&group_key);
+    sp1_zkvm::io::commit(&randomized_key.unwrap_or([0u8; 32]));
     sp1_zkvm::io::commit(&message);
 }
+
+/// The group key and, for a rerandomized signature, the one-time key, as 32-byte arrays
+fn keys(combined_sig: &CombinedSignature) -> Option<([u8; 32], Option<[u8; 32]>)> {
+    let group_key = <[u8; 32]>::try_from(combined_sig.public_key.as_slice()).ok()?;
+    let randomized_key = match &combined_sig.randomized_public_key {
+        Some(key) => Some(<[u8; 32]>::try_from(key.as_slice()).ok()?),
+        None => None,
+    };
+    Some((group_key, randomized_key))
+}
+
+/// Verify the signature inside zkVM
+fn verify(message: &[u8], public_key: &[u8; 32], signature: &[u8]) -> bool {
+    let Ok(verifying_key) = VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return false;
+    };
+
+    verifying_key.verify(message, &signature).is_ok()
+}
@@ -15,6 +15,18 @@ cd program
 cargo prove build
 cd ..
 
+# Build the signer daemon
+echo "Building signer daemon..."
+cd signer
+cargo build --release
+cd ..
+
+# Build the command-line tool (embeds the guest ELF for prove/verify-proof)
+echo "Building threshold-cli..."
+cd cli
+cargo build --release
+cd ..
+
 # Build the host program
 echo "Building SP1 host program..."
 cd host
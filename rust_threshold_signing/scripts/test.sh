@@ -6,7 +6,7 @@ echo "Testing Rust components..."
 # Test library
 echo "Testing threshold signing library..."
 cd lib
-cargo test
+cargo test --all-features
 cd ..
 
 # Test host
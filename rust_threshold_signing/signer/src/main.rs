@@ -0,0 +1,295 @@
+use frost_ed25519 as frost;
+use sha2::{Digest, Sha256};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use threshold_signing_lib::keystore;
+use threshold_signing_lib::transport::{DEFAULT_TCP_TIMEOUT, read_frame, write_frame};
+use threshold_signing_lib::{DerivationPath, SignerMessage, SignerResponse, ThresholdSigner, decode};
+
+const USAGE: &str = "Usage: threshold-signing-signer --key <keystore_file> [--password-file <path>] \
+(--listen <host:port> | --unix <socket_path>)\n\
+The keystore password is read from --password-file or the THRESHOLD_SIGNER_PASSWORD environment variable.\n\
+The daemon does not authenticate coordinators: it signs for anyone who can connect. Listen on a loopback \
+address or a Unix socket, and reach remote signers through a TLS or SSH tunnel.";
+
+/// Environment variable consulted when no --password-file is given
+const PASSWORD_ENV: &str = "THRESHOLD_SIGNER_PASSWORD";
+
+/// Ciphersuite of the key packages this daemon serves
+type Suite = frost::Ed25519Sha512;
+
+/// Where the daemon accepts coordinator connections
+enum Endpoint {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(String),
+}
+
+struct Config {
+    key_path: String,
+    password_file: Option<String>,
+    endpoint: Endpoint,
+}
+
+fn main() {
+    let config = parse_args(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(2);
+    });
+
+    let mut signer = load_signer(&config).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    let result = match &config.endpoint {
+        Endpoint::Tcp(addr) => serve_tcp(&mut signer, addr),
+        #[cfg(unix)]
+        Endpoint::Unix(path) => serve_unix(&mut signer, path),
+    };
+
+    if let Err(e) = result {
+        log(signer.index, &format!("fatal: {}", e));
+        process::exit(1);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+    let mut key_path = None;
+    let mut password_file = None;
+    let mut endpoint = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--key" => key_path = Some(value()?),
+            "--password-file" => password_file = Some(value()?),
+            "--listen" => endpoint = Some(Endpoint::Tcp(value()?)),
+            #[cfg(unix)]
+            "--unix" => endpoint = Some(Endpoint::Unix(value()?)),
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(Config {
+        key_path: key_path.ok_or("Missing --key")?,
+        password_file,
+        endpoint: endpoint.ok_or("Missing --listen or --unix")?,
+    })
+}
+
+/// Decrypt the signer's key package from its keystore file
+fn load_signer(config: &Config) -> Result<ThresholdSigner<Suite>, String> {
+    let password = match &config.password_file {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read password file {}: {}", path, e))?
+            .trim_end_matches(['\r', '\n'])
+            .to_string(),
+        None => std::env::var(PASSWORD_ENV)
+            .map_err(|_| format!("No --password-file given and {} is not set", PASSWORD_ENV))?,
+    };
+
+    keystore::load_signer::<Suite, _>(&config.key_path, password.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn serve_tcp(signer: &mut ThresholdSigner<Suite>, addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    log(signer.index, &format!("listening on tcp://{}", local_addr));
+    if !local_addr.ip().is_loopback() {
+        log(
+            signer.index,
+            "warning: listening beyond loopback without authentication; any peer that connects can get \
+             signatures (use a TLS/SSH tunnel or --unix)",
+        );
+    }
+
+    // One coordinator connection at a time: the signer holds per-session nonce state
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+        log(signer.index, &format!("connection from {}", peer));
+        if let Err(e) = handle_connection(signer, stream) {
+            log(signer.index, &format!("connection from {} failed: {}", peer, e));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn serve_unix(signer: &mut ThresholdSigner<Suite>, path: &str) -> io::Result<()> {
+    use std::os::unix::net::UnixListener;
+
+    use std::os::unix::fs::FileTypeExt;
+
+    // Remove a stale socket left behind by a previous run, but never anything
+    // else: a mistyped path could name the key file
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket; refusing to replace it", path),
+            ));
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let listener = UnixListener::bind(path)?;
+    log(signer.index, &format!("listening on unix://{}", path));
+
+    for stream in listener.incoming() {
+        log(signer.index, "local connection");
+        if let Err(e) = handle_connection(signer, stream?) {
+            log(signer.index, &format!("local connection failed: {}", e));
+        }
+    }
+    Ok(())
+}
+
+/// Connection whose reads and writes can be bounded in time
+trait TimedStream: Read + Write {
+    fn set_timeouts(&self, timeout: Duration) -> io::Result<()>;
+}
+
+impl TimedStream for TcpStream {
+    fn set_timeouts(&self, timeout: Duration) -> io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+#[cfg(unix)]
+impl TimedStream for std::os::unix::net::UnixStream {
+    fn set_timeouts(&self, timeout: Duration) -> io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+/// Answer requests on one connection until the coordinator disconnects
+///
+/// Connections are served one at a time, so one that stays idle for
+/// `DEFAULT_TCP_TIMEOUT` is dropped rather than blocking the daemon.
+fn handle_connection<S: TimedStream>(signer: &mut ThresholdSigner<Suite>, mut stream: S) -> io::Result<()> {
+    stream.set_timeouts(DEFAULT_TCP_TIMEOUT)?;
+    loop {
+        let request = match read_frame(&mut stream) {
+            Ok(request) => request,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "idle for too long, dropped"));
+            }
+            Err(e) => return Err(e),
+        };
+        let response = signer.receive_serialized_signing_request(&request);
+        for line in describe(&request, &response) {
+            log(signer.index, &line);
+        }
+        write_frame(&mut stream, &response)?;
+    }
+}
+
+/// Audit lines for one request: what was asked and what the signer answered
+///
+/// A batch gets one line per signing package, so every message signed is on
+/// record as it is for single requests.
+fn describe(request: &[u8], response: &[u8]) -> Vec<String> {
+    // The request comes from the network; the response was produced by our own signer
+    let Ok(request) = decode::<Suite, SignerMessage>(request) else {
+        return vec!["malformed request -> refused".to_string()];
+    };
+    let response: SignerResponse = decode::<Suite, _>(response).expect("Signer produced an undecodable response");
+
+    let action = match request {
+        SignerMessage::Round1Request { .. } => "round 1: nonce commitments".to_string(),
+        SignerMessage::Round2Request { signing_package, .. } => describe_package("round 2", &signing_package),
+        SignerMessage::RandomizedRound2Request { signing_package, .. } => {
+            describe_package("randomized round 2", &signing_package)
+        }
+        SignerMessage::DerivedRound2Request { signing_package, path, .. } => {
+            match DerivationPath::new(path) {
+                Ok(path) => describe_package(&format!("round 2 at {}", path), &signing_package),
+                Err(_) => "round 2: invalid derivation path".to_string(),
+            }
+        }
+        SignerMessage::PreprocessRequest { count, .. } => format!("preprocess: {} nonces", count),
+        SignerMessage::BatchRound2Request { signing_packages, .. } if !signing_packages.is_empty() => {
+            return describe_batch(&signing_packages, &response);
+        }
+        SignerMessage::BatchRound2Request { .. } => "batch round 2: no signing packages".to_string(),
+        SignerMessage::BatchRound1Request { count, .. } => format!("batch round 1: {} nonces", count),
+        SignerMessage::DiscardNoncesRequest { commitments, .. } => {
+            format!("discard: {} preprocessed nonces", commitments.len())
+        }
+    };
+
+    vec![format!("{} -> {}", action, outcome(&response))]
+}
+
+/// One line per package of a batch, each with its own entry of the batch response
+fn describe_batch(signing_packages: &[Vec<u8>], response: &SignerResponse) -> Vec<String> {
+    let count = signing_packages.len();
+    let outcomes: Vec<String> = match response {
+        SignerResponse::BatchSignatureShares { responses, .. } if responses.len() == count => responses
+            .iter()
+            .map(|r| outcome(&decode::<Suite, _>(r).expect("Signer produced an undecodable response")))
+            .collect(),
+        // Refused as a whole
+        other => vec![outcome(other); count],
+    };
+
+    signing_packages
+        .iter()
+        .zip(outcomes)
+        .enumerate()
+        .map(|(i, (signing_package, outcome))| {
+            let round = format!("batch round 2 ({}/{})", i + 1, count);
+            format!("{} -> {}", describe_package(&round, signing_package), outcome)
+        })
+        .collect()
+}
+
+/// What the signer answered, for the audit log
+fn outcome(response: &SignerResponse) -> String {
+    match response {
+        SignerResponse::Error { reason, .. } => format!("refused: {}", reason),
+        SignerResponse::SignatureShare { .. } => "signed".to_string(),
+        SignerResponse::Commitments { .. } => "committed".to_string(),
+        SignerResponse::PreprocessedCommitments { commitments, .. } => {
+            format!("published {} commitments", commitments.len())
+        }
+        SignerResponse::BatchSignatureShares { responses, .. } => {
+            let signed = responses
+                .iter()
+                .filter(|r| matches!(decode::<Suite, _>(r), Ok(SignerResponse::SignatureShare { .. })))
+                .count();
+            format!("signed {}, refused {}", signed, responses.len() - signed)
+        }
+        SignerResponse::NoncesDiscarded { discarded, .. } => format!("dropped {}", discarded),
+        SignerResponse::UnknownNonces { .. } => "refused: no unused nonces for this commitment".to_string(),
+    }
+}
+
+fn describe_package(round: &str, signing_package: &[u8]) -> String {
+    match frost::SigningPackage::deserialize(signing_package) {
+        Ok(package) => format!(
+            "{}: message sha256={} ({} bytes), {} participants",
+            round,
+            hex::encode(Sha256::digest(package.message())),
+            package.message().len(),
+            package.signing_commitments().len()
+        ),
+        Err(_) => format!("{}: undecodable signing package", round),
+    }
+}
+
+fn log(index: u16, line: &str) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    println!("[{}] [signer {}] {}", timestamp, index, line);
+}
@@ -7,9 +7,12 @@ contract ThresholdSignatureVerifier {
     ISP1Verifier public immutable verifier;
     bytes32 public immutable programVKey;
 
+    /// publicKey is the group key; randomizedPublicKey is the one-time key a
+    /// rerandomized signature verifies under, or zero for a plain signature
     event SignatureVerified(
         bool isValid,
         bytes32 publicKey,
+        bytes32 randomizedPublicKey,
         bytes message
     );
 
@@ -26,17 +29,18 @@ contract ThresholdSignatureVerifier {
         verifier.verifyProof(programVKey, publicValues, proof);
 
         // Decode public outputs
-        (bool isValid, bytes32 publicKey, bytes memory message) =
-            abi.decode(publicValues, (bool, bytes32, bytes));
+        (bool isValid, bytes32 publicKey, bytes32 randomizedPublicKey, bytes memory message) =
+            abi.decode(publicValues, (bool, bytes32, bytes32, bytes));
 
-        emit SignatureVerified(isValid, publicKey, message);
+        emit SignatureVerified(isValid, publicKey, randomizedPublicKey, message);
 
         return isValid;
     }
 
     function verifyThresholdSignatureView(
         bytes calldata publicValues
-    ) external pure returns (bool isValid, bytes32 publicKey, bytes memory message) {
-        (isValid, publicKey, message) = abi.decode(publicValues, (bool, bytes32, bytes));
+    ) external pure returns (bool isValid, bytes32 publicKey, bytes32 randomizedPublicKey, bytes memory message) {
+        (isValid, publicKey, randomizedPublicKey, message) =
+            abi.decode(publicValues, (bool, bytes32, bytes32, bytes));
     }
 }
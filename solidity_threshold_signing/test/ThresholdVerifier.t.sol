@@ -25,22 +25,25 @@ contract ThresholdVerifierTest is Test {
         // Create test data
         bool expectedIsValid = true;
         bytes32 expectedPublicKey = bytes32(uint256(0x123));
+        bytes32 expectedRandomizedKey = bytes32(uint256(0x456));
         bytes memory expectedMessage = "test message";
 
         // Encode the data
         bytes memory publicValues = abi.encode(
             expectedIsValid,
             expectedPublicKey,
+            expectedRandomizedKey,
             expectedMessage
         );
 
         // Call the view function
-        (bool isValid, bytes32 publicKey, bytes memory message) =
+        (bool isValid, bytes32 publicKey, bytes32 randomizedPublicKey, bytes memory message) =
             verifier.verifyThresholdSignatureView(publicValues);
 
         // Verify the decoded values
         assertEq(isValid, expectedIsValid);
         assertEq(publicKey, expectedPublicKey);
+        assertEq(randomizedPublicKey, expectedRandomizedKey);
         assertEq(message, expectedMessage);
     }
 }